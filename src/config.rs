@@ -1,24 +1,177 @@
 
+/// Which `OtpProvider` backs phone verification (`OTP_PROVIDER`)
+#[derive(Debug, Clone, PartialEq)]
+pub enum OtpProviderKind {
+    Twilio,
+    Local,
+    /// Self-hosted codes stored in MongoDB, delivered via `SMS_SENDER`
+    Mongo,
+}
+
+/// Which `SmsSender` delivers self-hosted codes (`SMS_SENDER`)
+#[derive(Debug, Clone, PartialEq)]
+pub enum SmsSenderKind {
+    Twilio,
+    Log,
+}
+
 #[derive(Debug, Clone)]
 pub struct Config{
     pub mongodb_uri: String,
-    pub jwt_secret: String,
+    /// `iss` and `aud` claims written to and required on access tokens
+    pub jwt_issuer: String,
+    pub jwt_audience: String,
+    /// Days a signing key is used before a new one is generated
+    pub jwt_key_rotation_days: i64,
     pub port: u16,
+    pub access_token_ttl_secs: i64,
+    pub refresh_token_ttl_days: i64,
+    pub otp_provider: OtpProviderKind,
+    pub local_otp_code: Option<String>,
+    pub sms_sender: SmsSenderKind,
+    pub otp_ttl_secs: i64,
+    pub otp_max_attempts: i32,
+    pub otp_resend_cooldown_secs: i64,
+    pub login_max_failures: i32,
+    pub login_ip_max_failures: i32,
+    pub login_backoff_base_secs: i64,
+    pub login_lockout_secs: i64,
+    /// Take the client IP from `X-Forwarded-For` (only when running behind a trusted proxy)
+    pub trust_forwarded_for: bool,
+    /// Extra seconds allowed past a question's timer for network latency
+    pub answer_grace_secs: i64,
+    /// Default lifetime of admin-issued registration invites
+    pub invite_ttl_hours: i64,
+    /// Region used for phone numbers entered without a `+<country code>` prefix
+    pub default_phone_region: phonenumber::country::Id,
+    /// Time zone whose midnight starts a new daily challenge
+    pub daily_challenge_timezone: chrono_tz::Tz,
+    /// Questions in each daily challenge (fewer when the category has fewer)
+    pub daily_challenge_questions: usize,
+    /// Hours without activity after which an unfinished quiz is marked abandoned
+    pub quiz_idle_timeout_hours: i64,
+    /// Days abandoned quizzes are kept before being deleted; kept forever when unset
+    pub abandoned_quiz_retention_days: Option<i64>,
 }
 
 impl Config {
     pub fn from_env() -> Self {
         let mongodb_uri = std::env::var("MONGODB_URI").expect("MONGODB_URI must be set");
-        let jwt_secret = std::env::var("JWT_SECRET").expect("JWT_SECRET must be set");
+        let jwt_issuer = std::env::var("JWT_ISSUER").unwrap_or_else(|_| "coding_quix_app".to_string());
+        let jwt_audience = std::env::var("JWT_AUDIENCE").unwrap_or_else(|_| "coding_quix_app".to_string());
+        let jwt_key_rotation_days = std::env::var("JWT_KEY_ROTATION_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .expect("JWT_KEY_ROTATION_DAYS must be a valid i64");
         let port = std::env::var("PORT")
             .unwrap_or_else(|_| "8000".to_string())
             .parse()
             .expect("PORT must be a valid u16");
+        let access_token_ttl_secs = std::env::var("ACCESS_TOKEN_TTL_SECS")
+            .unwrap_or_else(|_| "900".to_string())
+            .parse()
+            .expect("ACCESS_TOKEN_TTL_SECS must be a valid i64");
+        let refresh_token_ttl_days = std::env::var("REFRESH_TOKEN_TTL_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .expect("REFRESH_TOKEN_TTL_DAYS must be a valid i64");
+        let otp_provider = match std::env::var("OTP_PROVIDER").unwrap_or_else(|_| "twilio".to_string()).as_str() {
+            "twilio" => OtpProviderKind::Twilio,
+            "local" => OtpProviderKind::Local,
+            "mongo" => OtpProviderKind::Mongo,
+            other => panic!("OTP_PROVIDER must be `twilio`, `local` or `mongo`, got `{}`", other),
+        };
+        let local_otp_code = std::env::var("LOCAL_OTP_CODE").ok();
+        let sms_sender = match std::env::var("SMS_SENDER").unwrap_or_else(|_| "twilio".to_string()).as_str() {
+            "twilio" => SmsSenderKind::Twilio,
+            "log" => SmsSenderKind::Log,
+            other => panic!("SMS_SENDER must be `twilio` or `log`, got `{}`", other),
+        };
+        let otp_ttl_secs = std::env::var("OTP_TTL_SECS")
+            .unwrap_or_else(|_| "300".to_string())
+            .parse()
+            .expect("OTP_TTL_SECS must be a valid i64");
+        let otp_max_attempts = std::env::var("OTP_MAX_ATTEMPTS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .expect("OTP_MAX_ATTEMPTS must be a valid i32");
+        let otp_resend_cooldown_secs = std::env::var("OTP_RESEND_COOLDOWN_SECS")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()
+            .expect("OTP_RESEND_COOLDOWN_SECS must be a valid i64");
+        let login_max_failures = std::env::var("LOGIN_MAX_FAILURES")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse()
+            .expect("LOGIN_MAX_FAILURES must be a valid i32");
+        let login_ip_max_failures = std::env::var("LOGIN_IP_MAX_FAILURES")
+            .unwrap_or_else(|_| "50".to_string())
+            .parse()
+            .expect("LOGIN_IP_MAX_FAILURES must be a valid i32");
+        let login_backoff_base_secs = std::env::var("LOGIN_BACKOFF_BASE_SECS")
+            .unwrap_or_else(|_| "1".to_string())
+            .parse()
+            .expect("LOGIN_BACKOFF_BASE_SECS must be a valid i64");
+        let login_lockout_secs = std::env::var("LOGIN_LOCKOUT_SECS")
+            .unwrap_or_else(|_| "900".to_string())
+            .parse()
+            .expect("LOGIN_LOCKOUT_SECS must be a valid i64");
+        let trust_forwarded_for = std::env::var("TRUST_FORWARDED_FOR")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false);
+        let answer_grace_secs = std::env::var("ANSWER_GRACE_SECS")
+            .unwrap_or_else(|_| "2".to_string())
+            .parse()
+            .expect("ANSWER_GRACE_SECS must be a valid i64");
+        let invite_ttl_hours = std::env::var("INVITE_TTL_HOURS")
+            .unwrap_or_else(|_| "72".to_string())
+            .parse()
+            .expect("INVITE_TTL_HOURS must be a valid i64");
+        let default_phone_region = std::env::var("DEFAULT_PHONE_REGION")
+            .unwrap_or_else(|_| "NG".to_string())
+            .parse()
+            .expect("DEFAULT_PHONE_REGION must be an ISO 3166-1 alpha-2 region code");
+        let daily_challenge_timezone = std::env::var("DAILY_CHALLENGE_TIMEZONE")
+            .unwrap_or_else(|_| "UTC".to_string())
+            .parse()
+            .expect("DAILY_CHALLENGE_TIMEZONE must be an IANA time zone name, e.g. `Africa/Lagos`");
+        let daily_challenge_questions = std::env::var("DAILY_CHALLENGE_QUESTIONS")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse()
+            .expect("DAILY_CHALLENGE_QUESTIONS must be a valid usize");
+        let quiz_idle_timeout_hours = std::env::var("QUIZ_IDLE_TIMEOUT_HOURS")
+            .unwrap_or_else(|_| "24".to_string())
+            .parse()
+            .expect("QUIZ_IDLE_TIMEOUT_HOURS must be a valid i64");
+        let abandoned_quiz_retention_days = std::env::var("ABANDONED_QUIZ_RETENTION_DAYS")
+            .ok()
+            .map(|days| days.parse().expect("ABANDONED_QUIZ_RETENTION_DAYS must be a valid i64"));
 
         Config {
             mongodb_uri,
-            jwt_secret,
+            jwt_issuer,
+            jwt_audience,
+            jwt_key_rotation_days,
             port,
+            access_token_ttl_secs,
+            refresh_token_ttl_days,
+            otp_provider,
+            local_otp_code,
+            sms_sender,
+            otp_ttl_secs,
+            otp_max_attempts,
+            otp_resend_cooldown_secs,
+            login_max_failures,
+            login_ip_max_failures,
+            login_backoff_base_secs,
+            login_lockout_secs,
+            trust_forwarded_for,
+            answer_grace_secs,
+            invite_ttl_hours,
+            default_phone_region,
+            daily_challenge_timezone,
+            daily_challenge_questions,
+            quiz_idle_timeout_hours,
+            abandoned_quiz_retention_days,
         }
     }
 }
\ No newline at end of file
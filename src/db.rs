@@ -1,7 +1,152 @@
-use mongodb::{Client, Database};
+use std::time::Duration;
+
+use bson::doc;
+use mongodb::{options::IndexOptions, Client, Database, IndexModel};
 
 
 pub async fn init_db(uri: &str) -> Database {
     let client = Client::with_uri_str(uri).await.expect("Failed to connect to MongoDB");
     client.database("quiz_db")
-}
\ No newline at end of file
+}
+
+/// Create the indexes the services rely on (idempotent, safe to run on every start).
+pub async fn ensure_indexes(db: &Database) -> mongodb::error::Result<()> {
+    // Refresh tokens: looked up by family/user for revocation, purged by MongoDB once expired
+    db.collection::<bson::Document>("refresh_tokens")
+        .create_indexes(vec![
+            IndexModel::builder().keys(doc! { "family_id": 1 }).build(),
+            IndexModel::builder().keys(doc! { "user_id": 1 }).build(),
+            IndexModel::builder()
+                .keys(doc! { "expires_at": 1 })
+                .options(IndexOptions::builder().expire_after(Duration::from_secs(0)).build())
+                .build(),
+        ])
+        .await?;
+
+    // Revoked access token ids only need to outlive the tokens themselves
+    db.collection::<bson::Document>("revoked_tokens")
+        .create_indexes(vec![
+            IndexModel::builder()
+                .keys(doc! { "jti": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! { "expires_at": 1 })
+                .options(IndexOptions::builder().expire_after(Duration::from_secs(0)).build())
+                .build(),
+        ])
+        .await?;
+
+    // Self-hosted OTP codes: one per phone, removed by MongoDB once expired
+    db.collection::<bson::Document>("otp_codes")
+        .create_indexes(vec![
+            IndexModel::builder()
+                .keys(doc! { "phone_number": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! { "expires_at": 1 })
+                .options(IndexOptions::builder().expire_after(Duration::from_secs(0)).build())
+                .build(),
+        ])
+        .await?;
+
+    // Signing keys: looked up by kid, removed once no token signed with them can still be live
+    db.collection::<bson::Document>("jwt_keys")
+        .create_indexes(vec![
+            IndexModel::builder()
+                .keys(doc! { "kid": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! { "expires_at": 1 })
+                .options(IndexOptions::builder().expire_after(Duration::from_secs(0)).build())
+                .build(),
+        ])
+        .await?;
+
+    // Registration invites are dropped once expired, used or not
+    db.collection::<bson::Document>("invites")
+        .create_indexes(vec![
+            IndexModel::builder()
+                .keys(doc! { "expires_at": 1 })
+                .options(IndexOptions::builder().expire_after(Duration::from_secs(0)).build())
+                .build(),
+        ])
+        .await?;
+
+    // Adaptive quiz ability: one estimate per user and category
+    db.collection::<bson::Document>("abilities")
+        .create_indexes(vec![
+            IndexModel::builder()
+                .keys(doc! { "user_id": 1, "category_id": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+        ])
+        .await?;
+
+    // Review schedule: one entry per user and question, read in due order
+    db.collection::<bson::Document>("review_states")
+        .create_indexes(vec![
+            IndexModel::builder()
+                .keys(doc! { "user_id": 1, "question_id": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! { "user_id": 1, "due_at": 1 })
+                .build(),
+        ])
+        .await?;
+
+    // Daily challenges: one per category and day
+    db.collection::<bson::Document>("daily_challenges")
+        .create_indexes(vec![
+            IndexModel::builder()
+                .keys(doc! { "category_id": 1, "date": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+        ])
+        .await?;
+
+    // Quizzes: one attempt per player at each daily challenge (other quizzes have no challenge
+    // id), exams looked up by deadline, and open or abandoned quizzes swept by status
+    db.collection::<bson::Document>("quizzes")
+        .create_indexes(vec![
+            IndexModel::builder()
+                .keys(doc! { "user_id": 1, "daily_challenge_id": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .unique(true)
+                        .partial_filter_expression(doc! { "daily_challenge_id": { "$exists": true } })
+                        .build(),
+                )
+                .build(),
+            IndexModel::builder()
+                .keys(doc! { "exam.deadline": 1 })
+                .options(IndexOptions::builder().sparse(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! { "status": 1, "abandoned_at": 1 })
+                .build(),
+            IndexModel::builder()
+                .keys(doc! { "status": 1, "last_activity_at": 1 })
+                .build(),
+        ])
+        .await?;
+
+    // Failed login counters: one per key, forgotten a day after the last failure
+    db.collection::<bson::Document>("login_attempts")
+        .create_indexes(vec![
+            IndexModel::builder()
+                .keys(doc! { "key": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! { "last_failure_at": 1 })
+                .options(IndexOptions::builder().expire_after(Duration::from_secs(24 * 60 * 60)).build())
+                .build(),
+        ])
+        .await?;
+
+    Ok(())
+}
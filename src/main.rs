@@ -22,26 +22,46 @@ mod middleware;
 #[openapi(
     paths(
         routes::health_check,
+        routes::well_known::jwks,
         routes::auth::confirm_register,
         routes::auth::start_verification,
         routes::auth::login,
+        routes::auth::refresh_token,
+        routes::auth::logout,
+        routes::auth::forgot_password,
+        routes::auth::reset_password,
         routes::user::get_user,
         routes::user::update_profile,
         routes::admin::create_category,
         routes::admin::create_question,
         routes::quiz::start_quiz,
+        routes::quiz::compose_quiz,
+        routes::quiz::start_exam,
+        routes::quiz::start_review,
+        routes::quiz::due_reviews,
+        routes::quiz::get_daily_challenge,
+        routes::quiz::start_daily_challenge,
+        routes::quiz::get_daily_leaderboard,
         routes::admin::top_user_for_category,
+        routes::admin::set_scoring_policy,
         routes::admin::get_categories_with_top_users,
         routes::quiz::submit_answer,
         routes::quiz::finish_quiz,
         routes::quiz::get_leaderboard,
         routes::quiz::pause_quiz,
+        routes::quiz::get_quiz,
+        routes::quiz::next_question,
+        routes::quiz::get_quiz_question,
         routes::admin::get_category,
         routes::admin::list_categories,
         routes::admin::delete_category,
         routes::admin::get_question,
         routes::admin::list_questions,
-        routes::admin::delete_question
+        routes::admin::delete_question,
+        routes::admin::unlock_user,
+        routes::admin::rotate_signing_key,
+        routes::admin::create_invite,
+        routes::admin::revoke_invite
 
     ),
     components(
@@ -49,7 +69,16 @@ mod middleware;
             routes::auth::ConfirmRegisterRequest,
             routes::auth::StartVerificationRequest,
             routes::auth::LoginRequest,
+            routes::auth::RefreshTokenRequest,
+            routes::auth::LogoutRequest,
+            routes::auth::ForgotPasswordRequest,
+            routes::auth::ResetPasswordRequest,
+            models::jwt_key::Jwks,
+            models::jwt_key::Jwk,
+            models::invite::CreateInviteRequest,
+            models::invite::CreateInviteResponse,
             services::user_service::LoginResponse,
+            services::token_service::TokenPair,
             // models::user::User,
             models::user::UserResponse,
             models::user::Role,
@@ -59,8 +88,30 @@ mod middleware;
             models::category::CreateCategoryMultipart,
             models::category::CategoryWithTopUserResponse,
             models::question::CreateQuestionRequest,
+            models::question::AnswerKey,
+            models::question::MatchRules,
+            models::scoring::ScoringPolicy,
+            models::scoring::DifficultyPoints,
+            models::scoring::TimeBonus,
+            models::scoring::StreakBonus,
+            models::category::SetScoringPolicyRequest,
+            models::quiz::SubmittedAnswer,
+            models::quiz::FinishQuizResponse,
+            models::quiz::AdaptiveState,
+            models::quiz::QuizComposition,
+            models::quiz::QuizMode,
+            models::quiz::QuizStatus,
+            models::quiz::DifficultyShare,
+            routes::quiz::ComposeQuizRequest,
+            routes::quiz::StartExamRequest,
+            routes::quiz::StartReviewRequest,
+            models::review::DueReviewsResponse,
+            models::daily_challenge::DailyChallengeResponse,
+            models::daily_challenge::DailyLeaderboardEntry,
             routes::quiz::SubmitAnswerRequest,
             routes::quiz::PauseQuizRequest,
+            routes::quiz::QuizErrorResponse,
+            routes::quiz::FinishQuizRequest,
             models::leaderboard::LeaderboardEntry,
             models::category::CategoryResponse,
             models::question::QuestionResponse,
@@ -91,6 +142,11 @@ impl Modify for SecurityAddon {
                 HttpBuilder::new()
                     .scheme(HttpAuthScheme::Bearer)
                     .bearer_format("JWT")
+                    .description(Some(
+                        "JWT access token. Operations that list a role (e.g. `Admin`) as a scope \
+                         require a token whose `role` claim matches; an empty scope list means any \
+                         authenticated user.",
+                    ))
                     .build(),
             ),
         );
@@ -116,6 +172,34 @@ async fn main() {
 
     let config = config::Config::from_env();
     let db = Arc::new(db::init_db(&config.mongodb_uri).await);
+    db::ensure_indexes(&db).await.expect("Failed to create MongoDB indexes");
+
+    // `coding_quix_app migrate-phone-numbers` rewrites stored phone numbers to E.164 and exits
+    if std::env::args().nth(1).as_deref() == Some("migrate-phone-numbers") {
+        migrate_phone_numbers(db, &config).await;
+        return;
+    }
+
+    // quizzes stored before statuses existed must have one before anything reads them, and
+    // open ones their last activity before the idle sweep looks for it
+    let quiz_service = services::quiz_service::QuizService::new(
+        db.clone(),
+        Arc::new(services::leaderboard_service::LeaderboardService::new(db.clone())),
+        &config,
+    );
+    let backfilled = quiz_service.backfill_statuses().await.expect("Failed to backfill quiz statuses");
+    if backfilled > 0 {
+        println!("Backfilled the status of {} quizzes", backfilled);
+    }
+    let backfilled = quiz_service.backfill_last_activity().await.expect("Failed to backfill quiz activity");
+    if backfilled > 0 {
+        println!("Backfilled the last activity of {} quizzes", backfilled);
+    }
+
+    spawn_reward_sweeper(db.clone(), &config);
+    spawn_daily_challenge_generator(db.clone(), &config);
+    spawn_exam_finisher(db.clone(), &config);
+    spawn_quiz_maintenance(db.clone(), &config);
 
     let app = Router::new()
        .merge(routes::init_routes(db.clone(), Arc::new(config.clone())))
@@ -130,9 +214,141 @@ async fn main() {
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
 
     // serve the router with the listener (axum::serve requires tokio + http1/http2 features)
-    axum::serve(listener, app).await.unwrap();
+    // peer addresses are needed for per-IP login throttling
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await.unwrap();
      
 }
 
+async fn migrate_phone_numbers(db: Arc<mongodb::Database>, config: &config::Config) {
+    let user_service = background_user_service(db, config);
+
+    let (updated, skipped) = user_service
+        .normalize_stored_phone_numbers()
+        .await
+        .expect("Phone number migration failed");
+    println!("Normalized {} phone numbers", updated);
+    for reason in skipped {
+        println!("Skipped {}", reason);
+    }
+}
+
+/// A user service for work outside request handling; it never sends OTPs.
+fn background_user_service(db: Arc<mongodb::Database>, config: &config::Config) -> services::user_service::UserService {
+    services::user_service::UserService::new(
+        db.clone(),
+        Arc::new(services::phone_verify::LocalOtpProvider::new(None)),
+        Arc::new(services::token_service::TokenService::new(
+            db.clone(),
+            Arc::new(config.clone()),
+            Arc::new(services::jwt_keys::JwtKeyStore::new(db.clone(), config)),
+        )),
+        Arc::new(services::invite_service::InviteService::new(db.clone(), config)),
+        Arc::new(services::login_throttle::LoginThrottle::new(db, config)),
+        config.default_phone_region,
+    )
+}
+
+/// Periodically finishes granting rewards for quizzes whose finish request failed part-way.
+fn spawn_reward_sweeper(db: Arc<mongodb::Database>, config: &config::Config) {
+    let user_service = background_user_service(db.clone(), config);
+    let quiz_service = services::quiz_service::QuizService::new(
+        db.clone(),
+        Arc::new(services::leaderboard_service::LeaderboardService::new(db)),
+        config,
+    );
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            match quiz_service.apply_pending_rewards(&user_service).await {
+                Ok(0) => {}
+                Ok(n) => println!("Applied pending rewards for {} quizzes", n),
+                Err(e) => eprintln!("Reward sweep failed: {}", e),
+            }
+        }
+    });
+}
+
+/// Finishes exams whose time is up and whose player never called finish.
+fn spawn_exam_finisher(db: Arc<mongodb::Database>, config: &config::Config) {
+    let user_service = background_user_service(db.clone(), config);
+    let quiz_service = services::quiz_service::QuizService::new(
+        db.clone(),
+        Arc::new(services::leaderboard_service::LeaderboardService::new(db)),
+        config,
+    );
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(10));
+        loop {
+            interval.tick().await;
+            match quiz_service.finish_expired_exams(&user_service).await {
+                Ok((finished, failed)) => {
+                    if finished > 0 {
+                        println!("Finished {} expired exams", finished);
+                    }
+                    for reason in failed {
+                        eprintln!("Could not finish {}", reason);
+                    }
+                }
+                Err(e) => eprintln!("Finishing expired exams failed: {}", e),
+            }
+        }
+    });
+}
+
+/// Marks quizzes left idle as abandoned and, when a retention period is configured, deletes
+/// them once it has passed.
+fn spawn_quiz_maintenance(db: Arc<mongodb::Database>, config: &config::Config) {
+    let quiz_service = services::quiz_service::QuizService::new(
+        db.clone(),
+        Arc::new(services::leaderboard_service::LeaderboardService::new(db)),
+        config,
+    );
+    let idle = chrono::Duration::hours(config.quiz_idle_timeout_hours);
+    let retention = config.abandoned_quiz_retention_days.map(chrono::Duration::days);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(15 * 60));
+        loop {
+            interval.tick().await;
+            match quiz_service.abandon_idle_quizzes(idle).await {
+                Ok(0) => {}
+                Ok(n) => println!("Marked {} idle quizzes abandoned", n),
+                Err(e) => eprintln!("Abandoning idle quizzes failed: {}", e),
+            }
+            if let Some(retention) = retention {
+                match quiz_service.purge_abandoned_quizzes(retention).await {
+                    Ok(0) => {}
+                    Ok(n) => println!("Deleted {} abandoned quizzes", n),
+                    Err(e) => eprintln!("Deleting abandoned quizzes failed: {}", e),
+                }
+            }
+        }
+    });
+}
+
+/// Keeps today's and tomorrow's daily challenges generated, so they are ready before the day
+/// boundary and never drawn while players are waiting.
+fn spawn_daily_challenge_generator(db: Arc<mongodb::Database>, config: &config::Config) {
+    let daily_challenge_service = services::daily_challenge_service::DailyChallengeService::new(db, config);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(15 * 60));
+        loop {
+            interval.tick().await;
+            let today = daily_challenge_service.today();
+            for date in [Some(today), today.succ_opt()].into_iter().flatten() {
+                match daily_challenge_service.generate_all(date).await {
+                    Ok(0) => {}
+                    Ok(n) => println!("Generated {} daily challenges for {}", n, date),
+                    Err(e) => eprintln!("Daily challenge generation for {} failed: {}", date, e),
+                }
+            }
+        }
+    });
+}
+
 #[cfg(test)]
 mod tests;
@@ -1,18 +1,21 @@
 use axum::{
+    extract::State,
     http::{header::AUTHORIZATION, Request, StatusCode},
     middleware::Next,
     response::Response,
 };
 use std::sync::Arc;
 
-use crate::config::Config;
-use crate::utils::validate_jwt; // adjust path if Claims lives elsewhere
+use crate::models::user::Role;
+use crate::services::token_service::TokenService;
+use crate::utils::Claims; // adjust path if Claims lives elsewhere
 
-/// Middleware compatible with `middleware::from_fn`.
+/// Middleware compatible with `middleware::from_fn_with_state` (state: `Arc<TokenService>`).
 /// - Reads `Authorization: Bearer <token>` header (plain string parsing).
-/// - Tries to read JWT secret from request extensions (Arc<Config>) or falls back to env var `JWT_SECRET`.
-/// - Validates token, inserts `Arc<Claims>` into request extensions on success.
+/// - Validates the token and rejects it if its `jti` has been revoked (logout, reuse detection).
+/// - Inserts `Arc<Claims>` into request extensions on success.
 pub async fn auth_middleware(
+    State(token_service): State<Arc<TokenService>>,
     mut req: Request<axum::body::Body>,
     next: Next,
 ) -> Result<Response, StatusCode> {
@@ -32,21 +35,32 @@ pub async fn auth_middleware(
         None => return Err(StatusCode::UNAUTHORIZED),
     };
 
-    // 3) Obtain jwt_secret: prefer Config in request extensions, otherwise fallback to env
-    let jwt_secret = if let Some(cfg) = req.extensions().get::<Arc<Config>>() {
-        cfg.jwt_secret.clone()
-    } else if let Ok(s) = std::env::var("JWT_SECRET") {
-        s
-    } else {
-        return Err(StatusCode::INTERNAL_SERVER_ERROR);
-    };
-
-    // 4) Validate and insert claims (store Arc<Claims> to avoid Clone bounds)
-    match validate_jwt(token, &jwt_secret) {
-        Some(claims) => {
+    // 3) Validate (signature, expiry, revocation) and insert claims (store Arc<Claims> to avoid Clone bounds)
+    match token_service.validate_access_token(token).await {
+        Ok(Some(claims)) => {
             req.extensions_mut().insert(Arc::new(claims));
             Ok(next.run(req).await)
         }
-        None => Err(StatusCode::UNAUTHORIZED),
+        Ok(None) => Err(StatusCode::UNAUTHORIZED),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
+
+/// Authorization guard for admin-only routes, compatible with `middleware::from_fn`.
+/// - Must run after `auth_middleware`, which inserts `Arc<Claims>` into request extensions.
+/// - Responds with 401 when no claims are present and 403 when the caller is not `Role::Admin`.
+pub async fn require_admin(
+    req: Request<axum::body::Body>,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let claims = req
+        .extensions()
+        .get::<Arc<Claims>>()
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    if claims.role != Role::Admin.to_string() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(next.run(req).await)
+}
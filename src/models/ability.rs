@@ -0,0 +1,15 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A player's ability estimate in a category, carried from one adaptive quiz to the next
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CategoryAbility {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: ObjectId,
+    pub category_id: ObjectId,
+    pub rating: f64,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub updated_at: DateTime<Utc>,
+}
@@ -4,7 +4,7 @@ use utoipa::ToSchema;
 
 
 
-use super::user::UserResponse;
+use super::{scoring::ScoringPolicy, user::UserResponse};
 
 /// DB model (keep as-is, used for MongoDB)
 #[derive(Serialize, Deserialize, Clone, Debug,)]
@@ -16,6 +16,9 @@ pub struct Category {
     pub parent_id: Option<ObjectId>,
     pub image_url: Option<String>,
     pub top_user_id: Option<ObjectId>,
+    /// Scoring for quizzes in this category; the default policy when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scoring_policy: Option<ScoringPolicy>,
 }
 
 
@@ -36,9 +39,13 @@ pub struct CategoryResponse {
     pub tags: Vec<String>,
     pub parent_id: Option<String>,
     pub image_url: Option<String>,
+    pub scoring_policy: Option<ScoringPolicy>,
+}
 
-
-
+/// Request DTO for changing a category's scoring; `null` restores the default policy
+#[derive(Deserialize, ToSchema)]
+pub struct SetScoringPolicyRequest {
+    pub scoring_policy: Option<ScoringPolicy>,
 }
 
 /// Response DTO for a category with its top scoring user
@@ -64,6 +71,7 @@ impl From<(Category, Option<String>)> for CategoryResponse {
             image_url: c.image_url,
             parent_id: c.parent_id.map(|oid| oid.to_hex()),
             tags: c.tags,
+            scoring_policy: c.scoring_policy,
             // top_user is handled separately in the service layer
        
         }
@@ -79,6 +87,7 @@ impl From<CreateCategoryRequest> for Category {
             parent_id: req.parent_id.and_then(|id_str| ObjectId::parse_str(&id_str).ok()),
             image_url: None,
             top_user_id: None,
+            scoring_policy: None,
         }
     }
 }
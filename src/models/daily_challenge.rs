@@ -0,0 +1,62 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// The questions every player of a category gets on one day
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DailyChallenge {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub category_id: ObjectId,
+    /// Local date in `timezone`, as YYYY-MM-DD
+    pub date: String,
+    pub timezone: String,
+    pub questions: Vec<ObjectId>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub starts_at: DateTime<Utc>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub ends_at: DateTime<Utc>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct DailyChallengeResponse {
+    pub id: String,
+    pub category_id: String,
+    pub date: String,
+    pub timezone: String,
+    pub num_questions: usize,
+    /// RFC3339; when the challenge opens and closes
+    pub starts_at: String,
+    pub ends_at: String,
+    /// The caller's attempt, once they have started one
+    pub your_quiz_id: Option<String>,
+}
+
+impl From<(DailyChallenge, Option<ObjectId>)> for DailyChallengeResponse {
+    fn from((challenge, quiz_id): (DailyChallenge, Option<ObjectId>)) -> Self {
+        DailyChallengeResponse {
+            id: challenge.id.map(|id| id.to_hex()).unwrap_or_default(),
+            category_id: challenge.category_id.to_hex(),
+            date: challenge.date,
+            timezone: challenge.timezone,
+            num_questions: challenge.questions.len(),
+            starts_at: challenge.starts_at.to_rfc3339(),
+            ends_at: challenge.ends_at.to_rfc3339(),
+            your_quiz_id: quiz_id.map(|id| id.to_hex()),
+        }
+    }
+}
+
+/// A finished attempt at a daily challenge; ties on score go to the faster player
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct DailyLeaderboardEntry {
+    #[schema(value_type = String)]
+    pub user_id: ObjectId,
+    pub score: i32,
+    /// Seconds from starting to finishing the attempt, not counting pauses
+    pub time_secs: i64,
+    pub rank: i32,
+}
@@ -0,0 +1,41 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::user::Role;
+
+/// DB model for a single-use registration invite granting `role` (the raw code is never stored)
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Invite {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub code_hash: String,
+    pub role: Role,
+    pub created_by: ObjectId,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub expires_at: DateTime<Utc>,
+    /// Set when the invite is redeemed, together with `used_at`
+    pub used_by: Option<ObjectId>,
+    #[serde(default, with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional")]
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateInviteRequest {
+    pub role: Role,
+    /// Defaults to `INVITE_TTL_HOURS`
+    pub expires_in_hours: Option<i64>,
+}
+
+/// Returned once on creation; the code cannot be recovered later
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct CreateInviteResponse {
+    pub id: String,
+    pub code: String,
+    pub role: Role,
+    #[schema(value_type = String, format = DateTime)]
+    pub expires_at: DateTime<Utc>,
+}
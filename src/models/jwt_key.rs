@@ -0,0 +1,56 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// DB model for an Ed25519 key used to sign access tokens.
+/// The private key is stored as-is, so access to the `jwt_keys` collection must be restricted.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct JwtKey {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub kid: String,
+    /// PKCS#8 document, base64
+    pub private_key: String,
+    /// Raw public key, base64url (the JWK `x` parameter)
+    pub public_key: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    /// No new tokens are signed with the key after this
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub retires_at: DateTime<Utc>,
+    /// Tokens signed with the key are rejected, and the key is unpublished, after this
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Public key in JWK form (RFC 8037 OKP key)
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct Jwk {
+    pub kty: String,
+    pub crv: String,
+    pub x: String,
+    pub kid: String,
+    #[serde(rename = "use")]
+    pub key_use: String,
+    pub alg: String,
+}
+
+/// JSON Web Key Set served at `/.well-known/jwks.json`
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct Jwks {
+    pub keys: Vec<Jwk>,
+}
+
+impl From<&JwtKey> for Jwk {
+    fn from(key: &JwtKey) -> Self {
+        Self {
+            kty: "OKP".to_string(),
+            crv: "Ed25519".to_string(),
+            x: key.public_key.clone(),
+            kid: key.kid.clone(),
+            key_use: "sig".to_string(),
+            alg: "EdDSA".to_string(),
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// DB model tracking failed logins for one throttle key (`account:<phone>` or `ip:<addr>`)
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LoginAttempts {
+    pub key: String,
+    pub failures: i32,
+    #[serde(default, with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional")]
+    pub locked_until: Option<DateTime<Utc>>,
+    /// Drives the TTL index, so an idle counter eventually resets itself
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub last_failure_at: DateTime<Utc>,
+}
@@ -2,4 +2,13 @@ pub mod user;
 pub mod category;
 pub mod question;
 pub mod quiz;
-pub mod leaderboard;
\ No newline at end of file
+pub mod scoring;
+pub mod ability;
+pub mod review;
+pub mod daily_challenge;
+pub mod leaderboard;
+pub mod token;
+pub mod otp;
+pub mod login_attempt;
+pub mod jwt_key;
+pub mod invite;
\ No newline at end of file
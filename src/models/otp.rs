@@ -0,0 +1,19 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// DB model for a self-hosted one-time code (one active code per phone number)
+#[derive(Serialize, Deserialize, Clone)]
+pub struct OtpCode {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub phone_number: String,
+    /// Argon2 hash of the code; the plain code only ever leaves through the SMS sender
+    pub code_hash: String,
+    pub attempts: i32,
+    pub consumed: bool,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub expires_at: DateTime<Utc>,
+}
@@ -12,7 +12,14 @@ pub struct Question {
     pub question: String,
     pub question_type: QuestionType,
     pub options: Vec<String>,
+    /// Human-readable form of the answer, shown in reviews
     pub correct_answer: String,
+    /// How answers are graded; questions stored before answer keys existed grade against `correct_answer`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer_key: Option<AnswerKey>,
+    /// How strictly free-text answers are compared; the type's default when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_rules: Option<MatchRules>,
     pub explanation: String,
     pub difficulty: Difficulty,
     #[serde(with = "duration_as_secs")]
@@ -20,16 +27,192 @@ pub struct Question {
     pub tags: Vec<String>,
 }
 
+impl Question {
+    pub fn answer_key(&self) -> AnswerKey {
+        self.answer_key
+            .clone()
+            .unwrap_or_else(|| AnswerKey::Exact { answer: self.correct_answer.clone() })
+    }
+
+    pub fn match_rules(&self) -> MatchRules {
+        self.match_rules.clone().unwrap_or_else(|| MatchRules::default_for(self.question_type))
+    }
+}
+
 /// Question type enum (safe for OpenAPI)
-#[derive(Serialize, Deserialize, Clone, ToSchema)]
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, ToSchema)]
 pub enum QuestionType {
     MultipleChoice,
     TrueFalse,
     CodePrediction,
+    /// Pick every correct option
+    MultiSelect,
+    /// Arrange the options (e.g. lines of code) in the right order
+    Ordering,
+    /// Fill in one or more blanks in the question text
+    FillInTheBlank,
+    /// A number, accepted within a tolerance
+    Numeric,
+}
+
+/// The correct answer of a question, in the shape its type needs
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, ToSchema)]
+#[serde(tag = "type")]
+pub enum AnswerKey {
+    /// MultipleChoice, TrueFalse and CodePrediction
+    Exact { answer: String },
+    MultiSelect { answers: Vec<String> },
+    Ordering { order: Vec<String> },
+    /// Accepted answers for each blank, in order, compared under the question's match rules
+    FillInTheBlank { blanks: Vec<Vec<String>> },
+    Numeric { value: f64, tolerance: f64 },
+}
+
+impl AnswerKey {
+    /// Check the key fits the question type and its options.
+    pub fn validate(&self, question_type: QuestionType, options: &[String]) -> Result<(), String> {
+        let is_option = |answer: &String| options.contains(answer);
+        match (question_type, self) {
+            (QuestionType::MultipleChoice | QuestionType::TrueFalse, AnswerKey::Exact { answer }) => {
+                if !is_option(answer) {
+                    return Err("Answer must be one of the options".to_string());
+                }
+            }
+            (QuestionType::CodePrediction, AnswerKey::Exact { answer }) => {
+                if answer.trim().is_empty() {
+                    return Err("Answer must not be empty".to_string());
+                }
+            }
+            (QuestionType::MultiSelect, AnswerKey::MultiSelect { answers }) => {
+                if answers.is_empty() || !answers.iter().all(is_option) {
+                    return Err("Answers must be a non-empty subset of the options".to_string());
+                }
+                if has_duplicates(answers) {
+                    return Err("Answers must not repeat".to_string());
+                }
+            }
+            (QuestionType::Ordering, AnswerKey::Ordering { order }) => {
+                if order.len() < 2 || has_duplicates(order) {
+                    return Err("Order must list at least two distinct items".to_string());
+                }
+                if order.len() != options.len() || !order.iter().all(is_option) {
+                    return Err("Order must contain exactly the options".to_string());
+                }
+                // options are shown as stored, so they must not give the answer away
+                if order.as_slice() == options {
+                    return Err("Options must not already be in the correct order".to_string());
+                }
+            }
+            (QuestionType::FillInTheBlank, AnswerKey::FillInTheBlank { blanks }) => {
+                if blanks.is_empty() {
+                    return Err("At least one blank is required".to_string());
+                }
+                if blanks.iter().any(|accepted| accepted.iter().all(|a| a.trim().is_empty())) {
+                    return Err("Every blank needs at least one accepted answer".to_string());
+                }
+            }
+            (QuestionType::Numeric, AnswerKey::Numeric { value, tolerance }) => {
+                if !value.is_finite() || !tolerance.is_finite() || *tolerance < 0.0 {
+                    return Err("Value must be a number and tolerance a non-negative number".to_string());
+                }
+            }
+            (question_type, _) => {
+                return Err(format!("Answer key does not match question type {:?}", question_type));
+            }
+        }
+        Ok(())
+    }
+
+    /// Human-readable answer for reviews
+    pub fn display(&self) -> String {
+        match self {
+            AnswerKey::Exact { answer } => answer.clone(),
+            AnswerKey::MultiSelect { answers } => answers.join(", "),
+            AnswerKey::Ordering { order } => order.join("\n"),
+            AnswerKey::FillInTheBlank { blanks } => blanks
+                .iter()
+                .map(|accepted| accepted.first().cloned().unwrap_or_default())
+                .collect::<Vec<_>>()
+                .join(", "),
+            AnswerKey::Numeric { value, tolerance } if *tolerance > 0.0 => format!("{} (±{})", value, tolerance),
+            AnswerKey::Numeric { value, .. } => value.to_string(),
+        }
+    }
+}
+
+/// Matching rules for free-text answers (`CodePrediction` and other exact answers, and blanks)
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, ToSchema)]
+pub struct MatchRules {
+    /// Ignore leading and trailing whitespace, including a trailing newline
+    #[serde(default)]
+    pub trim: bool,
+    /// Treat any run of whitespace as a single space; implies `trim`
+    #[serde(default)]
+    pub collapse_whitespace: bool,
+    #[serde(default)]
+    pub case_insensitive: bool,
+    /// Further answers accepted besides the answer key, for exact answers
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Also accept any answer the regex matches in full, for exact answers
+    #[serde(default)]
+    pub pattern: Option<String>,
+}
+
+/// Longest accepted `pattern`, to keep regex compilation cheap
+const MAX_PATTERN_LEN: usize = 512;
+
+impl MatchRules {
+    /// Exact comparison, except for blanks which ignore case and surrounding spaces
+    pub fn default_for(question_type: QuestionType) -> Self {
+        match question_type {
+            QuestionType::FillInTheBlank => MatchRules { trim: true, case_insensitive: true, ..Default::default() },
+            _ => MatchRules::default(),
+        }
+    }
+
+    pub fn validate(&self, key: &AnswerKey) -> Result<(), String> {
+        let exact = matches!(key, AnswerKey::Exact { .. });
+        if !exact && (!self.aliases.is_empty() || self.pattern.is_some()) {
+            return Err("Aliases and patterns only apply to single free-text answers".to_string());
+        }
+        if let Some(pattern) = &self.pattern {
+            if pattern.len() > MAX_PATTERN_LEN {
+                return Err(format!("Pattern must be at most {} characters", MAX_PATTERN_LEN));
+            }
+            self.regex(pattern)?;
+        }
+        Ok(())
+    }
+
+    /// The form two answers are compared in
+    pub fn normalize(&self, text: &str) -> String {
+        let text = if self.collapse_whitespace {
+            text.split_whitespace().collect::<Vec<_>>().join(" ")
+        } else if self.trim {
+            text.trim().to_string()
+        } else {
+            text.to_string()
+        };
+        if self.case_insensitive { text.to_lowercase() } else { text }
+    }
+
+    /// `pattern` anchored to match the whole (normalized) answer
+    pub fn regex(&self, pattern: &str) -> Result<regex::Regex, String> {
+        regex::RegexBuilder::new(&format!("^(?:{})$", pattern))
+            .case_insensitive(self.case_insensitive)
+            .size_limit(1 << 20)
+            .build()
+            .map_err(|e| format!("Invalid pattern: {}", e))
+    }
+}
+
+fn has_duplicates(items: &[String]) -> bool {
+    items.iter().enumerate().any(|(i, item)| items[..i].contains(item))
 }
 
 /// Difficulty enum (safe for OpenAPI)
-#[derive(Serialize, Deserialize, Clone, ToSchema)]
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, ToSchema)]
 pub enum Difficulty {
     Beginner,
     Intermediate,
@@ -44,7 +227,10 @@ pub struct CreateQuestionRequest {
     pub text: String,
     pub question_type: QuestionType,
     pub options: Vec<String>,
-    pub correct_answer: String,
+    /// Must match `question_type`
+    pub answer: AnswerKey,
+    /// Defaults to exact matching (blanks: ignoring case and surrounding spaces)
+    pub match_rules: Option<MatchRules>,
     pub explanation: String,
     pub difficulty: Difficulty,
     pub timer_secs: i64,
@@ -60,6 +246,8 @@ pub struct QuestionResponse {
     pub question_type: QuestionType,
     pub options: Vec<String>,
     pub correct_answer: String,
+    pub answer_key: AnswerKey,
+    pub match_rules: MatchRules,
     pub explanation: String,
     pub difficulty: Difficulty,
     pub timer_secs: i64,
@@ -68,6 +256,8 @@ pub struct QuestionResponse {
 
 impl From<Question> for QuestionResponse {
     fn from(q: Question) -> Self {
+        let answer_key = q.answer_key();
+        let match_rules = q.match_rules();
         QuestionResponse {
             id: q.id.map(|oid| oid.to_hex()),
             category_id: q.category_id.to_hex(),
@@ -75,6 +265,8 @@ impl From<Question> for QuestionResponse {
             question_type: q.question_type,
             options: q.options,
             correct_answer: q.correct_answer,
+            answer_key,
+            match_rules,
             explanation: q.explanation,
             difficulty: q.difficulty,
             timer_secs: q.timer.num_seconds(),
@@ -89,6 +281,10 @@ impl TryFrom<CreateQuestionRequest> for Question {
     fn try_from(req: CreateQuestionRequest) -> Result<Self, Self::Error> {
         let category_oid = ObjectId::parse_str(&req.category_id)
             .map_err(|_| "Invalid category_id ObjectId".to_string())?;
+        req.answer.validate(req.question_type, &req.options)?;
+        if let Some(rules) = &req.match_rules {
+            rules.validate(&req.answer)?;
+        }
 
         Ok(Question {
             id: None,
@@ -96,7 +292,9 @@ impl TryFrom<CreateQuestionRequest> for Question {
             question: req.text,
             question_type: req.question_type,
             options: req.options,
-            correct_answer: req.correct_answer,
+            correct_answer: req.answer.display(),
+            answer_key: Some(req.answer),
+            match_rules: req.match_rules,
             explanation: req.explanation,
             difficulty: req.difficulty,
             timer: Duration::seconds(req.timer_secs),
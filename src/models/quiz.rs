@@ -6,7 +6,7 @@ use uuid::Uuid;
 use chrono::DateTime;
 use chrono::Utc;
 
-use crate::models::question::Difficulty;
+use crate::models::{question::{Difficulty, QuestionType}, scoring::ScoringPolicy};
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Quiz {
@@ -18,19 +18,259 @@ pub struct Quiz {
     pub difficulty: Difficulty,
     pub questions: Vec<ObjectId>, 
     pub answers: Vec<UserAnswer>,  
+    /// When each question was first served, i.e. when its timer started
+    #[serde(default)]
+    pub served: Vec<ServedQuestion>,
+    /// Questions must be answered in order; otherwise any served question may be answered
+    #[serde(default = "default_sequential")]
+    pub sequential: bool,
     pub start_time: DateTime<Utc>,
+    /// When the quiz was finished; only set once its status is `Finished`
     pub end_time: Option<DateTime<Utc>>,
     pub score: i32,
-    pub paused: bool,
+    #[serde(default)]
+    pub status: QuizStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional")]
+    pub abandoned_at: Option<DateTime<Utc>>,
+    /// When the player last served, answered, paused or resumed, kept so the idle sweep can
+    /// query for it; missing on quizzes stored before it was tracked
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional")]
+    pub last_activity_at: Option<DateTime<Utc>>,
+    /// Every pause, the open one last while the quiz is `Paused`
+    #[serde(default)]
+    pub pauses: Vec<PauseInterval>,
+    /// False between finishing the quiz and having granted its XP, history and leaderboard credit
+    #[serde(default)]
+    pub rewards_applied: bool,
+    /// Bumped by every answer, pause and finish; those writes only apply to the version they read
+    #[serde(default)]
+    pub version: i64,
+    /// The policy the quiz is scored under, fixed when it starts
+    #[serde(default)]
+    pub scoring: ScoringPolicy,
+    /// Present for adaptive quizzes, whose questions are drawn one at a time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adaptive: Option<AdaptiveState>,
+    /// Where the questions were drawn from; `category_id` is the first of its categories
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub composition: Option<QuizComposition>,
+    #[serde(default)]
+    pub mode: QuizMode,
+    /// The daily challenge this quiz is the player's attempt at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_challenge_id: Option<ObjectId>,
+    /// When that challenge's day ends; answers after it are refused and a later finish counts
+    /// as finishing then
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional")]
+    pub challenge_ends_at: Option<DateTime<Utc>>,
+    /// Present for exams, which are finished automatically once their time is up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exam: Option<ExamLimit>,
+}
+
+/// Where a quiz stands
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default, ToSchema)]
+pub enum QuizStatus {
+    #[default]
+    InProgress,
+    Paused,
+    Finished,
+    /// Left idle for too long; closed without rewards and left out of every stat
+    Abandoned,
+}
+
+impl QuizStatus {
+    /// Whether the quiz can no longer be played
+    pub fn is_closed(self) -> bool {
+        matches!(self, QuizStatus::Finished | QuizStatus::Abandoned)
+    }
+}
+
+/// What a quiz is played for
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default, ToSchema)]
+pub enum QuizMode {
+    /// Counts towards the category leaderboards
+    #[default]
+    Standard,
+    /// Questions due in the player's review schedule; earns XP but no leaderboard credit
+    Review,
+    /// A day's shared challenge, ranked on its own daily leaderboard
+    Daily,
+}
+
+impl QuizMode {
+    /// Whether the quiz's score counts towards the category leaderboards
+    pub fn ranked(self) -> bool {
+        self == QuizMode::Standard
+    }
+
+    /// How often, and for how long in total, a quiz of this mode may be paused
+    pub fn pause_limits(self) -> PauseLimits {
+        match self {
+            QuizMode::Standard => PauseLimits { max_pauses: 3, max_total: Duration::minutes(10) },
+            QuizMode::Review => PauseLimits { max_pauses: 10, max_total: Duration::hours(1) },
+            // everyone plays the same questions, so pausing is kept to a short break
+            QuizMode::Daily => PauseLimits { max_pauses: 1, max_total: Duration::minutes(2) },
+        }
+    }
+}
+
+pub struct PauseLimits {
+    pub max_pauses: usize,
+    pub max_total: Duration,
+}
+
+/// One pause of a quiz. Time paused past `allowance_ends_at` is not taken off the clock, so
+/// staying paused beyond the mode's limit gains nothing.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PauseInterval {
+    /// The question that was on screen, i.e. served but not yet answered
+    pub question_id: Option<ObjectId>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub paused_at: DateTime<Utc>,
+    #[serde(default, with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional")]
+    pub resumed_at: Option<DateTime<Utc>>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub allowance_ends_at: DateTime<Utc>,
+}
+
+/// Whole-quiz time limit of an exam
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ExamLimit {
+    pub time_limit_secs: i64,
+    /// `start_time` plus the time limit; no answers are taken after it
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub deadline: DateTime<Utc>,
+}
+
+/// What a quiz draws its questions from
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct QuizComposition {
+    /// The first category is the quiz's primary one, credited on the leaderboard
+    #[schema(value_type = Vec<String>)]
+    pub category_ids: Vec<ObjectId>,
+    /// Also draw from the categories' subcategories, at any depth
+    pub include_subcategories: bool,
+    /// Only questions with at least one of these tags; any question when empty
+    pub tags: Vec<String>,
+    /// Share of the questions at each difficulty, in percent adding up to 100
+    pub difficulty_mix: Vec<DifficultyShare>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct DifficultyShare {
+    pub difficulty: Difficulty,
+    pub percent: u32,
+}
+
+/// Progress of an adaptive quiz
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct AdaptiveState {
+    /// Number of questions the quiz will have once complete
+    pub length: usize,
+    /// Current Elo-style ability estimate of the player
+    pub ability: f64,
+}
+
+fn default_sequential() -> bool {
+    true
+}
+
+impl Quiz {
+    pub fn answer_for(&self, question_id: ObjectId) -> Option<&UserAnswer> {
+        self.answers.iter().find(|a| a.question_id == question_id)
+    }
+
+    pub fn served_at(&self, question_id: ObjectId) -> Option<DateTime<Utc>> {
+        self.served.iter().find(|s| s.question_id == question_id).map(|s| s.served_at)
+    }
+
+    /// Number of questions the quiz has, or will have once an adaptive quiz has drawn them all
+    pub fn total_questions(&self) -> usize {
+        self.adaptive.as_ref().map_or(self.questions.len(), |a| a.length)
+    }
+
+    /// Time taken off the clock for pauses between `from` and `to`
+    pub fn paused_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Duration {
+        self.pauses
+            .iter()
+            .map(|pause| {
+                let start = pause.paused_at.max(from);
+                let end = pause.resumed_at.unwrap_or(to).min(pause.allowance_ends_at).min(to);
+                (end - start).max(Duration::zero())
+            })
+            .fold(Duration::zero(), |total, paused| total + paused)
+    }
+
+    /// When the player last did anything with the quiz
+    pub fn last_activity(&self) -> DateTime<Utc> {
+        let served = self.served.iter().map(|s| s.served_at);
+        let answered = self.answers.iter().filter_map(|a| a.answered_at);
+        let paused = self.pauses.iter().flat_map(|p| [Some(p.paused_at), p.resumed_at]).flatten();
+        served.chain(answered).chain(paused).chain(self.last_activity_at).fold(self.start_time, DateTime::max)
+    }
+
+    /// The most recently served question that is still unanswered
+    pub fn question_on_screen(&self) -> Option<ObjectId> {
+        self.served
+            .iter()
+            .rev()
+            .map(|s| s.question_id)
+            .find(|&q| self.answer_for(q).is_none())
+    }
+
+    /// First question, in quiz order, that has not been answered yet
+    pub fn current_question(&self) -> Option<ObjectId> {
+        self.questions.iter().copied().find(|&q| self.answer_for(q).is_none())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ServedQuestion {
+    pub question_id: ObjectId,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub served_at: DateTime<Utc>,
+}
+
+/// An answer as given by the player: text for single-answer and code questions, a list for
+/// multi-select, ordering and fill-in-the-blank, a number for numeric questions
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, ToSchema)]
+#[serde(untagged)]
+pub enum SubmittedAnswer {
+    Number(f64),
+    Text(String),
+    List(Vec<String>),
+}
+
+impl From<&str> for SubmittedAnswer {
+    fn from(answer: &str) -> Self {
+        SubmittedAnswer::Text(answer.to_string())
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct UserAnswer {
     pub question_id: ObjectId,
-    pub answer: String,
+    /// None for questions an exam ran out of time on
+    pub answer: Option<SubmittedAnswer>,
+    /// Measured by the server, from serving the question to receiving the answer
     #[serde(with = "duration_as_secs")]
     pub time_taken: Duration,
+    /// As reported by the client; kept for analysis, never used for scoring
+    #[serde(default, with = "duration_as_secs_optional")]
+    pub client_time_taken: Option<Duration>,
+    #[serde(default, with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional")]
+    pub served_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional")]
+    pub answered_at: Option<DateTime<Utc>>,
     pub correct: bool,
+    /// Arrived after the question's timer (plus grace) ran out; scores nothing
+    #[serde(default)]
+    pub late: bool,
+    /// Points the answer earned (negative when penalized); absent on answers from before
+    /// scoring policies
+    #[serde(default)]
+    pub points: Option<i32>,
 }
 
 mod duration_as_secs {
@@ -53,6 +293,28 @@ mod duration_as_secs {
     }
 }
 
+mod duration_as_secs_optional {
+    use chrono::Duration;
+    use serde::{self, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(dur: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match dur {
+            Some(dur) => serializer.serialize_some(&dur.num_seconds()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Option::<i64>::deserialize(deserializer)?.map(Duration::seconds))
+    }
+}
+
 
 
 
@@ -60,9 +322,12 @@ mod duration_as_secs {
 #[derive(Serialize, ToSchema)]
 pub struct UserAnswerResponse {
     pub question_id: String, // hex string
-    pub answer: String,
+    pub answer: Option<SubmittedAnswer>,
     pub time_taken_secs: i64,
+    pub client_time_taken_secs: Option<i64>,
     pub correct: bool,
+    pub late: bool,
+    pub points: Option<i32>,
 }
 
 
@@ -78,8 +343,68 @@ pub struct QuizResponse {
     pub answers: Vec<UserAnswerResponse>,
     pub start_time: String,          // RFC3339 string
     pub end_time: Option<String>,    // RFC3339 string or null
+    pub abandoned_at: Option<String>,
     pub score: i32,
-    pub paused: bool,
+    pub status: QuizStatus,
+    pub pause_count: usize,
+    /// Seconds taken off the clock for pauses so far
+    pub paused_secs: i64,
+    pub sequential: bool,
+    /// Send back as `version` to make a change only if nothing else changed the quiz meanwhile
+    pub version: i64,
+    pub scoring: ScoringPolicy,
+    pub adaptive: Option<AdaptiveState>,
+    pub composition: Option<QuizComposition>,
+    pub mode: QuizMode,
+    pub daily_challenge_id: Option<String>,
+    /// Exams only: the time limit, and when it runs out (RFC3339)
+    pub time_limit_secs: Option<i64>,
+    pub deadline: Option<String>,
+}
+
+/// Result of finishing a quiz
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct FinishQuizResponse {
+    pub quiz_id: String,
+    pub score: i32,
+    pub correct_answers: usize,
+    pub total_questions: usize,
+    /// Final ability estimate, for adaptive quizzes
+    pub ability: Option<f64>,
+    /// Difficulty level closest to `ability`
+    pub ability_level: Option<Difficulty>,
+}
+
+/// A quiz question as the player sees it; the answer key stays hidden until it is reviewable
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct QuizQuestionResponse {
+    pub quiz_id: String,
+    /// Position in the quiz, starting at 0
+    pub index: usize,
+    pub total: usize,
+    pub question_id: String,
+    pub text: String,
+    pub question_type: QuestionType,
+    pub options: Vec<String>,
+    /// Number of blanks to fill, for fill-in-the-blank questions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blanks: Option<usize>,
+    pub difficulty: Difficulty,
+    pub timer_secs: i64,
+    /// RFC3339; when the question's timer started
+    pub served_at: Option<String>,
+    /// Only present once the question has been answered or the quiz is finished
+    pub review: Option<QuestionReview>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct QuestionReview {
+    /// None when the quiz ended before the question was answered
+    pub your_answer: Option<SubmittedAnswer>,
+    pub correct: bool,
+    pub late: bool,
+    pub correct_answer: String,
+    pub explanation: String,
 }
 
 #[derive(Serialize, ToSchema)]
@@ -95,13 +420,17 @@ impl From<UserAnswer> for UserAnswerResponse {
             question_id: a.question_id.to_hex(),
             answer: a.answer,
             time_taken_secs: a.time_taken.num_seconds(),
+            client_time_taken_secs: a.client_time_taken.map(|d| d.num_seconds()),
             correct: a.correct,
+            late: a.late,
+            points: a.points,
         }
     }
 }
 
 impl From<Quiz> for QuizResponse {
     fn from(q: Quiz) -> Self {
+        let paused_secs = q.paused_between(q.start_time, q.end_time.unwrap_or_else(Utc::now)).num_seconds();
         QuizResponse {
             id: q.id.map(|oid| oid.to_hex()),
             uuid: q.uuid.to_string(),
@@ -112,8 +441,20 @@ impl From<Quiz> for QuizResponse {
             answers: q.answers.into_iter().map(|a| a.into()).collect(),
             start_time: q.start_time.to_rfc3339(),
             end_time: q.end_time.map(|d| d.to_rfc3339()),
+            abandoned_at: q.abandoned_at.map(|d| d.to_rfc3339()),
             score: q.score,
-            paused: q.paused,
+            status: q.status,
+            pause_count: q.pauses.len(),
+            paused_secs,
+            sequential: q.sequential,
+            version: q.version,
+            scoring: q.scoring,
+            adaptive: q.adaptive,
+            composition: q.composition,
+            mode: q.mode,
+            daily_challenge_id: q.daily_challenge_id.map(|id| id.to_hex()),
+            time_limit_secs: q.exam.as_ref().map(|e| e.time_limit_secs),
+            deadline: q.exam.map(|e| e.deadline.to_rfc3339()),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,45 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::services::spaced_repetition::Schedule;
+
+/// A question in a user's review schedule, put there by a wrong or slow answer
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReviewState {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: ObjectId,
+    pub question_id: ObjectId,
+    pub category_id: ObjectId,
+    pub ease_factor: f64,
+    pub interval_days: i64,
+    pub repetitions: u32,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub due_at: DateTime<Utc>,
+    /// When the answer the schedule was last updated from was given; older answers are ignored
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub last_reviewed_at: DateTime<Utc>,
+}
+
+impl ReviewState {
+    pub fn schedule(&self) -> Schedule {
+        Schedule {
+            ease_factor: self.ease_factor,
+            interval_days: self.interval_days,
+            repetitions: self.repetitions,
+        }
+    }
+}
+
+/// How much reviewing a user has waiting
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct DueReviewsResponse {
+    /// Questions due for review now
+    pub due: u64,
+    /// Questions in the user's review schedule, due or not
+    pub scheduled: u64,
+    /// RFC3339; when the next question not yet due becomes due
+    pub next_due_at: Option<String>,
+}
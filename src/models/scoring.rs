@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::question::Difficulty;
+
+/// How answers are turned into points. Set per category by admins and copied onto each quiz
+/// when it starts, so a quiz's score can always be explained by the policy it was played under.
+/// The default reproduces the original fixed scoring.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct ScoringPolicy {
+    /// Points for a correct answer
+    pub base_points: DifficultyPoints,
+    pub time_bonus: TimeBonus,
+    /// Rewards consecutive correct answers; none when absent
+    #[serde(default)]
+    pub streak: Option<StreakBonus>,
+    /// Points taken away for a wrong answer (not for a late one)
+    #[serde(default)]
+    pub wrong_penalty: i32,
+    /// Award part of the points for partly correct multi-select, ordering and
+    /// fill-in-the-blank answers instead of nothing
+    #[serde(default)]
+    pub partial_credit: bool,
+}
+
+impl Default for ScoringPolicy {
+    fn default() -> Self {
+        Self {
+            base_points: DifficultyPoints { beginner: 5, intermediate: 10, advanced: 20, expert: 30 },
+            time_bonus: TimeBonus::Flat { points: 10, within_fraction: 0.5 },
+            streak: None,
+            wrong_penalty: 0,
+            partial_credit: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct DifficultyPoints {
+    pub beginner: i32,
+    pub intermediate: i32,
+    pub advanced: i32,
+    pub expert: i32,
+}
+
+impl DifficultyPoints {
+    pub fn for_difficulty(&self, difficulty: &Difficulty) -> i32 {
+        match difficulty {
+            Difficulty::Beginner => self.beginner,
+            Difficulty::Intermediate => self.intermediate,
+            Difficulty::Advanced => self.advanced,
+            Difficulty::Expert => self.expert,
+        }
+    }
+}
+
+/// Extra points for answering a correct answer quickly
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, ToSchema)]
+#[serde(tag = "type")]
+pub enum TimeBonus {
+    None,
+    /// `points` when answered within `within_fraction` of the question's timer
+    Flat { points: i32, within_fraction: f64 },
+    /// From `max_points` for an instant answer down to nothing when the timer runs out
+    LinearDecay { max_points: i32 },
+}
+
+/// Multiplies the points of a correct answer by `1 + step * streak`, capped at
+/// `max_multiplier`, where `streak` counts the correct answers right before it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct StreakBonus {
+    pub step: f64,
+    pub max_multiplier: f64,
+}
+
+impl ScoringPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        let base = &self.base_points;
+        if [base.beginner, base.intermediate, base.advanced, base.expert].iter().any(|&p| p < 0) {
+            return Err("Base points must not be negative".to_string());
+        }
+        if self.wrong_penalty < 0 {
+            return Err("Wrong answer penalty must not be negative".to_string());
+        }
+        match self.time_bonus {
+            TimeBonus::Flat { points, within_fraction } if points < 0 || !(0.0..=1.0).contains(&within_fraction) => {
+                return Err("Flat time bonus needs non-negative points and a fraction between 0 and 1".to_string());
+            }
+            TimeBonus::LinearDecay { max_points } if max_points < 0 => {
+                return Err("Time bonus must not be negative".to_string());
+            }
+            _ => {}
+        }
+        let valid_streak = |s: &StreakBonus| s.step >= 0.0 && s.max_multiplier >= 1.0 && s.max_multiplier.is_finite();
+        if self.streak.as_ref().is_some_and(|s| !valid_streak(s)) {
+            return Err("Streak step must not be negative and its cap must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// DB model for an issued refresh token (the raw token is never stored, only its hash)
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RefreshToken {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: ObjectId,
+    /// Every token produced by rotating the same login shares a family
+    pub family_id: Uuid,
+    pub token_hash: String,
+    /// `jti` of the access token issued alongside this refresh token
+    pub access_jti: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub expires_at: DateTime<Utc>,
+    /// Set once the token has been exchanged; presenting it again is a reuse
+    pub used_at: Option<DateTime<Utc>>,
+    pub revoked: bool,
+}
+
+/// DB model for a revoked access token, kept until the token would have expired anyway
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RevokedToken {
+    pub jti: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub expires_at: DateTime<Utc>,
+}
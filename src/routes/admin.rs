@@ -1,13 +1,13 @@
 use std::{fs, sync::Arc};
 
-use axum::{extract::{Path, State}, http::{StatusCode, HeaderMap}, Json, Router};
+use axum::{extract::{Path, State}, http::{StatusCode, HeaderMap}, middleware, Extension, Json, Router};
 // use axum::extract::Multipart;
 use axum_extra::extract::Multipart;
 use bson::oid::ObjectId;
 use uuid::Uuid;
 use tower_http::services::ServeDir;
 
-use crate::{models::{category::{Category, CategoryResponse, CreateCategoryMultipart, CreateCategoryResponse, CategoryWithTopUserResponse}, question::{CreateQuestionRequest, CreateQuestionResponse, Question, QuestionResponse}, user::UserResponse}, services::{question_service::QuestionService, quiz_service::QuizService, user_service::UserService}, };
+use crate::{models::{category::{Category, CategoryResponse, CreateCategoryMultipart, CreateCategoryResponse, CategoryWithTopUserResponse, SetScoringPolicyRequest}, question::{CreateQuestionRequest, CreateQuestionResponse, Question, QuestionResponse}, user::UserResponse, invite::{CreateInviteRequest, CreateInviteResponse}}, middleware::auth::{auth_middleware, require_admin}, utils::Claims, services::{question_service::QuestionService, quiz_service::QuizService, user_service::UserService}, };
 
 
 
@@ -18,9 +18,11 @@ use crate::{models::{category::{Category, CategoryResponse, CreateCategoryMultip
         ("id" = String, Path, description = "Category ID")
     ),
     responses(
-        (status = 200, description = "Top user for category", body = UserResponse)
+        (status = 200, description = "Top user for category", body = UserResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 403, description = "Admin role required")
     ),
-    security(("bearer_auth" = []))
+    security(("bearer_auth" = ["Admin"]))
 )]
 pub async fn top_user_for_category(
     State((quiz_service, user_service, _question_service)): State<(Arc<QuizService>, Arc<UserService>, Arc<QuestionService>)>,
@@ -38,9 +40,11 @@ pub async fn top_user_for_category(
     get,
     path = "/admin/categories_with_top_users",
     responses(
-        (status = 200, description = "List of categories with top users", body = [CategoryWithTopUserResponse])
+        (status = 200, description = "List of categories with top users", body = [CategoryWithTopUserResponse]),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 403, description = "Admin role required")
     ),
-    security(("bearer_auth" = []))
+    security(("bearer_auth" = ["Admin"]))
 )]
 pub async fn get_categories_with_top_users(
     State((quiz_service, user_service, _question_service)): State<(Arc<QuizService>, Arc<UserService>, Arc<QuestionService>)>,
@@ -62,11 +66,11 @@ pub async fn get_categories_with_top_users(
     ),
     responses(
         (status = 201, description = "Category created", body = CreateCategoryResponse),
-        (status = 400, description = "Bad request", body = String)
+        (status = 400, description = "Bad request", body = String),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 403, description = "Admin role required")
     ),
-    security(
-        ("bearer_auth" = [])
-    )
+    security(("bearer_auth" = ["Admin"]))
 )]
 pub async fn create_category(
     State((_quiz_service, _user_service, question_service)): State<(Arc<QuizService>, Arc<UserService>, Arc<QuestionService>)>,
@@ -131,6 +135,7 @@ let category = Category {
     parent_id: parent_oid,
     image_url: image_url.clone(),
     top_user_id: None,
+    scoring_policy: None,
 };
 
     question_service
@@ -145,6 +150,7 @@ let category = Category {
                     tags: category.tags,
                     parent_id: category.parent_id.map(|oid| oid.to_hex()),
                     image_url,
+                    scoring_policy: None,
                 },
             }))
         })
@@ -159,9 +165,11 @@ let category = Category {
     ),
     responses(
         (status = 200, description = "Category found", body = CategoryResponse),
-        (status = 404, description = "Category not found")
+        (status = 404, description = "Category not found"),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 403, description = "Admin role required")
     ),
-    security(("bearer_auth" = []))
+    security(("bearer_auth" = ["Admin"]))
 )]
 pub async fn get_category(
     State((_quiz_service, _user_service, question_service)): State<(Arc<QuizService>, Arc<UserService>, Arc<QuestionService>)>,
@@ -175,9 +183,11 @@ pub async fn get_category(
     get,
     path = "/admin/categories",
     responses(
-        (status = 200, description = "List of categories", body = [CategoryResponse])
+        (status = 200, description = "List of categories", body = [CategoryResponse]),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 403, description = "Admin role required")
     ),
-    security(("bearer_auth" = []))
+    security(("bearer_auth" = ["Admin"]))
 )]
 pub async fn list_categories(
     State((_quiz_service, _user_service, question_service)): State<(Arc<QuizService>, Arc<UserService>, Arc<QuestionService>)>,
@@ -193,9 +203,11 @@ pub async fn list_categories(
     ),
     responses(
         (status = 204, description = "Category deleted"),
-        (status = 404, description = "Category not found")
+        (status = 404, description = "Category not found"),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 403, description = "Admin role required")
     ),
-    security(("bearer_auth" = []))
+    security(("bearer_auth" = ["Admin"]))
 )]
 pub async fn delete_category(
     State((_quiz_service, _user_service, question_service)): State<(Arc<QuizService>, Arc<UserService>, Arc<QuestionService>)>,
@@ -210,32 +222,25 @@ pub async fn delete_category(
     path = "/admin/questions",
     request_body  = CreateQuestionRequest ,
     responses(
-        (status = 201, description = "Question created", body = String)
+        (status = 201, description = "Question created", body = String),
+        (status = 400, description = "Answer key missing, malformed or not matching the question type"),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 403, description = "Admin role required")
     ),
-    security(("bearer_auth" = []))
+    security(("bearer_auth" = ["Admin"]))
 )]
 pub async fn create_question(
     State((_quiz_service, _user_service, question_service)): State<(Arc<QuizService>, Arc<UserService>, Arc<QuestionService>)>, 
-    Json(question): Json<Question>
+    Json(req): Json<CreateQuestionRequest>
 ) -> Result<(StatusCode, Json<CreateQuestionResponse>), (StatusCode, String)> {
-    question_service.create_question(question.clone()).await.map(|_| {
-        (StatusCode::CREATED, Json(CreateQuestionResponse {
-            message: "Question created successfully".to_string(),
-            question: QuestionResponse {
-                id: question.id.map(|oid| oid.to_hex()),
-                category_id: question.category_id.to_hex(),
-                text: question.question,
-                question_type: question.question_type,
-                options: question.options,
-                correct_answer: question.correct_answer,
-                explanation: question.explanation,
-                difficulty: question.difficulty,
-                timer_secs: question.timer.num_seconds(),
-                tags: question.tags,
-            }
-        }))
-    })
-    .map_err(|e| (StatusCode::BAD_REQUEST, e))
+    let mut question = Question::try_from(req).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let id = question_service.create_question(question.clone()).await.map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    question.id = ObjectId::parse_str(&id).ok();
+
+    Ok((StatusCode::CREATED, Json(CreateQuestionResponse {
+        message: "Question created successfully".to_string(),
+        question: QuestionResponse::from(question),
+    })))
 }
 
 #[utoipa::path(
@@ -246,9 +251,11 @@ pub async fn create_question(
     ),
     responses(
         (status = 200, description = "Question found", body = QuestionResponse),
-        (status = 404, description = "Question not found")
+        (status = 404, description = "Question not found"),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 403, description = "Admin role required")
     ),
-    security(("bearer_auth" = []))
+    security(("bearer_auth" = ["Admin"]))
 )]
 pub async fn get_question(
     State((_quiz_service, _user_service, question_service)): State<(Arc<QuizService>, Arc<UserService>, Arc<QuestionService>)>,
@@ -265,9 +272,11 @@ pub async fn get_question(
         ("category_id" = Option<String>, Query, description = "Category ID to filter questions")
     ),
     responses(
-        (status = 200, description = "List of questions", body = [QuestionResponse])
+        (status = 200, description = "List of questions", body = [QuestionResponse]),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 403, description = "Admin role required")
     ),
-    security(("bearer_auth" = []))
+    security(("bearer_auth" = ["Admin"]))
 )]
 pub async fn list_questions(
     State((_quiz_service, _user_service, question_service)): State<(Arc<QuizService>, Arc<UserService>, Arc<QuestionService>)>,
@@ -285,9 +294,11 @@ pub async fn list_questions(
     ),
     responses(
         (status = 204, description = "Question deleted"),
-        (status = 404, description = "Question not found")
+        (status = 404, description = "Question not found"),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 403, description = "Admin role required")
     ),
-    security(("bearer_auth" = []))
+    security(("bearer_auth" = ["Admin"]))
 )]
 pub async fn delete_question(
     State((_quiz_service, _user_service, question_service)): State<(Arc<QuizService>, Arc<UserService>, Arc<QuestionService>)>,
@@ -298,6 +309,124 @@ pub async fn delete_question(
 }
 
 
+#[utoipa::path(
+    put,
+    path = "/admin/categories/{id}/scoring",
+    params(
+        ("id" = String, Path, description = "Category ID")
+    ),
+    request_body = SetScoringPolicyRequest,
+    responses(
+        (status = 200, description = "Scoring policy updated; applies to quizzes started from now on", body = CategoryResponse),
+        (status = 400, description = "Invalid category ID or policy"),
+        (status = 404, description = "Category not found"),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(("bearer_auth" = ["Admin"]))
+)]
+pub async fn set_scoring_policy(
+    State((_quiz_service, _user_service, question_service)): State<(Arc<QuizService>, Arc<UserService>, Arc<QuestionService>)>,
+    Path(id): Path<String>,
+    Json(req): Json<SetScoringPolicyRequest>,
+) -> Result<Json<CategoryResponse>, (StatusCode, String)> {
+    let category_id = ObjectId::parse_str(&id).map_err(|_| (StatusCode::BAD_REQUEST, "Invalid category ID".to_string()))?;
+    if let Some(policy) = &req.scoring_policy {
+        policy.validate().map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    }
+    question_service
+        .set_scoring_policy(category_id, req.scoring_policy)
+        .await
+        .map(|category| Json(CategoryResponse::from((category, None))))
+        .map_err(|e| (StatusCode::NOT_FOUND, e))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/users/{id}/unlock",
+    params(
+        ("id" = String, Path, description = "User ID")
+    ),
+    responses(
+        (status = 204, description = "Failed login counter and lockout cleared"),
+        (status = 404, description = "User not found"),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(("bearer_auth" = ["Admin"]))
+)]
+pub async fn unlock_user(
+    State((_quiz_service, user_service, _question_service)): State<(Arc<QuizService>, Arc<UserService>, Arc<QuestionService>)>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let user_id = ObjectId::parse_str(&id).map_err(|_| (StatusCode::BAD_REQUEST, "Invalid user ID".to_string()))?;
+    user_service.unlock_account(user_id).await.map(|_| StatusCode::NO_CONTENT).map_err(|e| (StatusCode::NOT_FOUND, e))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/jwt/rotate",
+    responses(
+        (status = 204, description = "New signing key generated; older keys stay published until their tokens expire"),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(("bearer_auth" = ["Admin"]))
+)]
+pub async fn rotate_signing_key(
+    State((_quiz_service, user_service, _question_service)): State<(Arc<QuizService>, Arc<UserService>, Arc<QuestionService>)>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    user_service.token_service.jwt_keys.rotate().await.map(|_| StatusCode::NO_CONTENT).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/invites",
+    request_body = CreateInviteRequest,
+    responses(
+        (status = 201, description = "Single-use invite created; the code is only shown once", body = CreateInviteResponse),
+        (status = 400, description = "Invalid expiry"),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(("bearer_auth" = ["Admin"]))
+)]
+pub async fn create_invite(
+    State((_quiz_service, user_service, _question_service)): State<(Arc<QuizService>, Arc<UserService>, Arc<QuestionService>)>,
+    Extension(claims): Extension<Arc<Claims>>,
+    Json(req): Json<CreateInviteRequest>,
+) -> Result<(StatusCode, Json<CreateInviteResponse>), (StatusCode, String)> {
+    let admin_id = ObjectId::parse_str(&claims.sub).map_err(|_| (StatusCode::UNAUTHORIZED, "Invalid token subject".to_string()))?;
+    user_service.invite_service.create(admin_id, req.role, req.expires_in_hours).await
+        .map(|invite| (StatusCode::CREATED, Json(invite)))
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/admin/invites/{id}",
+    params(
+        ("id" = String, Path, description = "Invite ID")
+    ),
+    responses(
+        (status = 204, description = "Invite revoked"),
+        (status = 404, description = "No unused invite with this ID"),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(("bearer_auth" = ["Admin"]))
+)]
+pub async fn revoke_invite(
+    State((_quiz_service, user_service, _question_service)): State<(Arc<QuizService>, Arc<UserService>, Arc<QuestionService>)>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let invite_id = ObjectId::parse_str(&id).map_err(|_| (StatusCode::BAD_REQUEST, "Invalid invite ID".to_string()))?;
+    match user_service.invite_service.revoke(invite_id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err((StatusCode::NOT_FOUND, "Invite not found or already used".to_string())),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+    }
+}
 
 
 pub fn admin_routes(
@@ -305,6 +434,7 @@ pub fn admin_routes(
     quiz_service: Arc<QuizService>,
     user_service: Arc<UserService>,
 ) -> Router {
+    let token_service = user_service.token_service.clone();
     let state = (quiz_service, user_service, question_service.clone());
 
     Router::new()
@@ -315,8 +445,16 @@ pub fn admin_routes(
         .route("/admin/categories/{id}", axum::routing::get(get_category).delete(delete_category))
         .route("/admin/questions", axum::routing::post(create_question).get(list_questions))
         .route("/admin/questions/{id}", axum::routing::get(get_question).delete(delete_question))
-        .nest_service("/uploads", ServeDir::new("uploads"))
         .route("/admin/categories/{id}/top_user", axum::routing::get(top_user_for_category))
+        .route("/admin/categories/{id}/scoring", axum::routing::put(set_scoring_policy))
         .route("/admin/categories_with_top_users", axum::routing::get(get_categories_with_top_users))
+        .route("/admin/users/{id}/unlock", axum::routing::post(unlock_user))
+        .route("/admin/jwt/rotate", axum::routing::post(rotate_signing_key))
+        .route("/admin/invites", axum::routing::post(create_invite))
+        .route("/admin/invites/{id}", axum::routing::delete(revoke_invite))
+        // layers run bottom-up: authenticate first, then require the Admin role
+        .route_layer(middleware::from_fn(require_admin))
+        .route_layer(middleware::from_fn_with_state(token_service, auth_middleware))
         .with_state(state)
+        .nest_service("/uploads", ServeDir::new("uploads"))
 }
@@ -1,15 +1,20 @@
-use axum::{extract::State, http::StatusCode, Json, Router};
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{header::RETRY_AFTER, HeaderMap, StatusCode},
+    middleware,
+    response::{IntoResponse, Response},
+    Extension, Json, Router,
+};
 use serde::Deserialize;
 use utoipa::ToSchema;
-use crate::{config, services::user_service::{LoginResponse, UserService}};
-use std::sync::Arc;
-use crate::models::user::Role;
+use crate::{config, middleware::auth::auth_middleware, services::{token_service::TokenPair, user_service::{LoginError, LoginResponse, UserService}}, utils::{normalize_phone, Claims}};
+use std::{net::SocketAddr, sync::Arc};
 
 
 
 #[derive(Deserialize, ToSchema)]
 pub struct StartVerificationRequest {
-    phone_number: String, // E.164 recommended
+    phone_number: String, // E.164, or national format in DEFAULT_PHONE_REGION
 }
 
 #[derive(Deserialize, ToSchema)]
@@ -28,43 +33,84 @@ pub struct LoginRequest {
     request_body = LoginRequest,
     responses(
     (status = 200, description = "Login successful", body = LoginResponse),
-        (status = 401, description = "Unauthorized", body = LoginResponse)
-    )
+        (status = 401, description = "Invalid phone number or password", body = LoginResponse),
+        (status = 400, description = "Invalid or non-mobile phone number", body = LoginResponse),
+        (status = 429, description = "Too many failed attempts; see Retry-After", body = LoginResponse)
+    ),
+    security(())
 )]
 pub async fn login(
     State((user_service, config)): State<(Arc<UserService>, Arc<config::Config>)>,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: HeaderMap,
     Json(req): Json<LoginRequest>,
-) -> Result<Json<LoginResponse>, (StatusCode, Json<LoginResponse>)> {
-    // map the service error into our (StatusCode, Json<LoginResponse>) error type,
+) -> Result<Json<LoginResponse>, Response> {
+    let client_ip = client_ip(&config, &headers, connect_info.map(|Extension(ConnectInfo(addr))| addr));
+    let phone_number = normalize_phone(&req.phone_number, config.default_phone_region).map_err(|e| {
+        let body = LoginResponse { message: e, token: None, refresh_token: None, user: None };
+        (StatusCode::BAD_REQUEST, Json(body)).into_response()
+    })?;
+
+    // map the service error into a status (+ Retry-After when locked) and a LoginResponse body,
     // then `?` to early-return on error.
-    let (user, token) = user_service
-        .login(req.phone_number, req.password, &config.jwt_secret)
+    let (user, tokens) = user_service
+        .login(phone_number, req.password, client_ip)
         .await
         .map_err(|e| {
-            let body = LoginResponse {
-                message: e,
+            let body = Json(LoginResponse {
+                message: e.to_string(),
                 token: None,
+                refresh_token: None,
                 user: None,
-            };
-            (StatusCode::UNAUTHORIZED, Json(body))
+            });
+            match e {
+                LoginError::InvalidCredentials => (StatusCode::UNAUTHORIZED, body).into_response(),
+                LoginError::Locked { retry_after_secs } => (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    [(RETRY_AFTER, retry_after_secs.to_string())],
+                    body,
+                ).into_response(),
+                LoginError::Internal(detail) => {
+                    eprintln!("Login failed: {}", detail);
+                    (StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+                }
+            }
         })?; // <-- important
 
     let body = LoginResponse {
         message: "Login successful".to_string(),
-        token: Some(token),
+        token: Some(tokens.access_token),
+        refresh_token: Some(tokens.refresh_token),
         user: Some(user),
     };
 
     Ok(Json(body))
 }
 
+/// Client address for login throttling: the first `X-Forwarded-For` hop when the proxy is
+/// trusted, otherwise the peer address of the connection.
+fn client_ip(config: &config::Config, headers: &HeaderMap, peer: Option<SocketAddr>) -> Option<String> {
+    if config.trust_forwarded_for {
+        let forwarded = headers
+            .get("x-forwarded-for")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(',').next())
+            .map(|ip| ip.trim().to_string());
+        if forwarded.is_some() {
+            return forwarded;
+        }
+    }
+    peer.map(|addr| addr.ip().to_string())
+}
+
 #[derive(Deserialize, ToSchema)]
 pub struct ConfirmRegisterRequest {
     phone_number: String,
     code: String,
     username: String,
     password: String,
-    role: Option<String>,
+    /// Invite from an admin; required to register with a role other than User
+    invite_code: Option<String>,
 }
 
 #[utoipa::path(
@@ -72,16 +118,19 @@ pub struct ConfirmRegisterRequest {
     path = "/start_verification",
     request_body = StartVerificationRequest,
     responses(
-        (status = 200, description = "OTP sent")
-    )
+        (status = 200, description = "OTP sent"),
+        (status = 400, description = "Invalid or non-mobile phone number")
+    ),
+    security(())
 )]
 
 pub async fn start_verification(
-    State((user_service, _config)): State<(Arc<UserService>, Arc<config::Config>)>,
+    State((user_service, config)): State<(Arc<UserService>, Arc<config::Config>)>,
     Json(req): Json<StartVerificationRequest>,
 ) -> Result<StatusCode, (StatusCode, String)> {
-    // optionally validate/normalize the phone here (phonenumber crate)
-    user_service.start_phone_verification(req.phone_number).await
+    let phone_number = normalize_phone(&req.phone_number, config.default_phone_region)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    user_service.start_phone_verification(phone_number).await
         .map(|_| StatusCode::OK)
         .map_err(|e| (StatusCode::BAD_REQUEST, e))
 }
@@ -91,28 +140,140 @@ pub async fn start_verification(
     path = "/confirm_register",
     request_body = ConfirmRegisterRequest,
     responses(
-        (status = 201, description = "User registered")
-    )
+        (status = 201, description = "User registered"),
+        (status = 400, description = "Invalid phone number, OTP or invite code, or phone/username taken")
+    ),
+    security(())
 )]
 pub async fn confirm_register(
-    State((user_service, _config)): State<(Arc<UserService>, Arc<config::Config>)>,
+    State((user_service, config)): State<(Arc<UserService>, Arc<config::Config>)>,
     Json(req): Json<ConfirmRegisterRequest>,
 ) -> Result<StatusCode, (StatusCode, String)> {
-    let role = match req.role.as_deref() {
-        Some("admin") => Role::Admin,
-        _ => Role::User,
-    };
-
-    user_service.verify_and_register(req.phone_number, req.code, req.username, req.password, role).await
+    let phone_number = normalize_phone(&req.phone_number, config.default_phone_region)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    user_service.verify_and_register(phone_number, req.code, req.username, req.password, req.invite_code).await
         .map(|_| StatusCode::CREATED)
         .map_err(|e| (StatusCode::BAD_REQUEST, e))
 }
 
+#[derive(Deserialize, ToSchema)]
+pub struct RefreshTokenRequest {
+    refresh_token: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct LogoutRequest {
+    refresh_token: Option<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/token/refresh",
+    request_body = RefreshTokenRequest,
+    responses(
+        (status = 200, description = "New access and refresh token", body = TokenPair),
+        (status = 401, description = "Refresh token invalid, expired, revoked or reused")
+    ),
+    security(())
+)]
+pub async fn refresh_token(
+    State((user_service, _config)): State<(Arc<UserService>, Arc<config::Config>)>,
+    Json(req): Json<RefreshTokenRequest>,
+) -> Result<Json<TokenPair>, (StatusCode, String)> {
+    user_service.token_service.refresh(&req.refresh_token).await
+        .map(Json)
+        .map_err(|e| (StatusCode::UNAUTHORIZED, e))
+}
+
+#[utoipa::path(
+    post,
+    path = "/logout",
+    request_body = LogoutRequest,
+    responses(
+        (status = 204, description = "Access token revoked, plus the refresh token's session if given"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn logout(
+    State((user_service, _config)): State<(Arc<UserService>, Arc<config::Config>)>,
+    Extension(claims): Extension<Arc<Claims>>,
+    Json(req): Json<LogoutRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    user_service.token_service.logout(&claims, req.refresh_token.as_deref()).await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ForgotPasswordRequest {
+    phone_number: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ResetPasswordRequest {
+    phone_number: String,
+    code: String,
+    new_password: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/password/forgot",
+    request_body = ForgotPasswordRequest,
+    responses(
+        (status = 202, description = "If the phone belongs to an account, an OTP has been sent"),
+        (status = 400, description = "Invalid or non-mobile phone number")
+    ),
+    security(())
+)]
+pub async fn forgot_password(
+    State((user_service, config)): State<(Arc<UserService>, Arc<config::Config>)>,
+    Json(req): Json<ForgotPasswordRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let phone_number = normalize_phone(&req.phone_number, config.default_phone_region)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    user_service.start_password_reset(phone_number).await
+        .map(|_| StatusCode::ACCEPTED)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+
+#[utoipa::path(
+    post,
+    path = "/password/reset",
+    request_body = ResetPasswordRequest,
+    responses(
+        (status = 204, description = "Password changed; all existing sessions revoked"),
+        (status = 400, description = "Invalid or expired code")
+    ),
+    security(())
+)]
+pub async fn reset_password(
+    State((user_service, config)): State<(Arc<UserService>, Arc<config::Config>)>,
+    Json(req): Json<ResetPasswordRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let phone_number = normalize_phone(&req.phone_number, config.default_phone_region)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    user_service.reset_password(phone_number, req.code, req.new_password).await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+
 
 pub fn auth_routes(user_service: Arc<UserService>, config: Arc<config::Config>) -> Router {
+    let token_service = user_service.token_service.clone();
+
     Router::new()
         .route("/start_verification", axum::routing::post(start_verification))
         .route("/confirm_register", axum::routing::post(confirm_register))
         .route("/login", axum::routing::post(login))
+        .route("/token/refresh", axum::routing::post(refresh_token))
+        .route("/password/forgot", axum::routing::post(forgot_password))
+        .route("/password/reset", axum::routing::post(reset_password))
+        .route(
+            "/logout",
+            axum::routing::post(logout)
+                .route_layer(middleware::from_fn_with_state(token_service, auth_middleware)),
+        )
         .with_state((user_service, config))
 }
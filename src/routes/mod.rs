@@ -4,19 +4,21 @@ use axum::{Router, routing::get};
 use std::sync::Arc;
 use mongodb::Database;
 
-use crate::{config, services::{phone_verify::TwilioClient, leaderboard_service::LeaderboardService}};
+use crate::{config, services::{phone_verify::otp_provider_from_config, leaderboard_service::LeaderboardService, token_service::TokenService, login_throttle::LoginThrottle, jwt_keys::JwtKeyStore, invite_service::InviteService}};
 
 pub mod auth;
 pub mod user;
 pub mod admin;
 pub mod quiz;
+pub mod well_known;
 
 #[utoipa::path(
     get,
     path = "/health",
     responses(
         (status = 200, description = "Health check OK")
-    )
+    ),
+    security(())
 )]
 async fn health_check() -> &'static str {
     "OK"
@@ -24,15 +26,27 @@ async fn health_check() -> &'static str {
 
 pub fn init_routes(db: Arc<Database>, config: Arc<config::Config>) -> Router {
     use crate::services::user_service::UserService;
-  let twilio = Arc::new(TwilioClient::new_from_env());
+    let otp = otp_provider_from_config(&config, db.clone()).expect("Failed to configure OTP provider");
     let question_service = Arc::new(crate::services::question_service::QuestionService::new(db.clone()));
     let leaderboard_service = Arc::new(LeaderboardService::new(db.clone()));
-
-    let user_service = Arc::new(UserService::new(db.clone(), twilio));
-    let quiz_service = Arc::new(crate::services::quiz_service::QuizService::new(db.clone(), leaderboard_service.clone()));
+    let jwt_keys = Arc::new(JwtKeyStore::new(db.clone(), &config));
+    let token_service = Arc::new(TokenService::new(db.clone(), config.clone(), jwt_keys.clone()));
+    let login_throttle = Arc::new(LoginThrottle::new(db.clone(), &config));
+    let invite_service = Arc::new(InviteService::new(db.clone(), &config));
+
+    let user_service = Arc::new(UserService::new(
+        db.clone(),
+        otp,
+        token_service,
+        invite_service,
+        login_throttle,
+        config.default_phone_region,
+    ));
+    let quiz_service = Arc::new(crate::services::quiz_service::QuizService::new(db.clone(), leaderboard_service.clone(), &config));
 
     Router::new()
         .route("/health", get(health_check))
+        .merge(well_known::well_known_routes(jwt_keys))
         .merge(auth::auth_routes(user_service.clone(), config.clone()))
         .merge(user::user_routes(user_service.clone()))
         .merge(admin::admin_routes(
@@ -1,34 +1,133 @@
 use axum::{
-    extract::{Path, State}, http::StatusCode, Extension, Json, Router
+    extract::{Path, Query, State}, http::StatusCode, response::{IntoResponse, Response}, Extension, Json, Router
 };
 use bson::oid::ObjectId;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use utoipa::ToSchema;
+use chrono::NaiveDate;
+use utoipa::{IntoParams, ToSchema};
 
 use crate::{middleware::auth::auth_middleware, utils::Claims};
 use crate::models::question::Difficulty;
-use crate::models::quiz::Quiz;
-use crate::services::{quiz_service::QuizService, user_service::UserService, };
+use crate::models::quiz::{DifficultyShare, FinishQuizResponse, Quiz, QuizComposition, QuizQuestionResponse, QuizResponse, SubmittedAnswer};
+use crate::services::{quiz_service::{Caller, QuizError, QuizService}, user_service::UserService, };
 use crate::models::leaderboard::LeaderboardEntry;
+use crate::models::review::DueReviewsResponse;
+use crate::models::daily_challenge::{DailyChallengeResponse, DailyLeaderboardEntry};
 
 #[derive(Deserialize, ToSchema)]
 pub struct StartQuizRequest {
     category_id: String,
     difficulty: Difficulty,
     num_questions: i32,
+    /// Answer questions strictly in order (default true)
+    sequential: Option<bool>,
+    /// Pick each question's difficulty from how the player is doing, starting at `difficulty`
+    /// (or the player's ability from earlier adaptive quizzes); always sequential
+    adaptive: Option<bool>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct StartExamRequest {
+    category_id: String,
+    difficulty: Difficulty,
+    num_questions: i32,
+    /// Seconds from starting until the exam is finished automatically (at most a day)
+    time_limit_secs: i64,
+    /// Answer questions strictly in order (default false, so questions can be skipped and
+    /// come back to)
+    sequential: Option<bool>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ComposeQuizRequest {
+    /// The first category is the quiz's primary one, credited on the leaderboard
+    category_ids: Vec<String>,
+    /// Also draw from subcategories (default true)
+    include_subcategories: Option<bool>,
+    /// Only questions with at least one of these tags
+    #[serde(default)]
+    tags: Vec<String>,
+    /// Percent of the questions at each difficulty, adding up to 100
+    difficulty_mix: Vec<DifficultyShare>,
+    num_questions: i32,
+    /// Answer questions strictly in order (default true)
+    sequential: Option<bool>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct StartReviewRequest {
+    /// Most questions to review (default 10, at most 50); fewer when fewer are due
+    num_questions: Option<i32>,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct DailyLeaderboardQuery {
+    /// Challenge day as YYYY-MM-DD (default today)
+    date: Option<String>,
 }
 
 #[derive(Deserialize, ToSchema)]
 pub struct SubmitAnswerRequest {
     question_id: String,
-    answer: String,
-    time_taken: i64,
+    answer: SubmittedAnswer,
+    /// Seconds the client thinks it took; informational only, timing is measured server-side
+    time_taken: Option<i64>,
+    /// Quiz version the client last saw; the answer is rejected with `stale_attempt` if it changed
+    version: Option<i64>,
 }
 
 #[derive(Deserialize, ToSchema)]
 pub struct PauseQuizRequest {
     paused: bool,
+    /// Quiz version the client last saw
+    version: Option<i64>,
+}
+
+/// Optional body of the finish endpoint
+#[derive(Deserialize, ToSchema)]
+pub struct FinishQuizRequest {
+    /// Quiz version the client last saw
+    version: Option<i64>,
+}
+
+/// Error body of the quiz endpoints; `code` is stable and meant for clients to branch on
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct QuizErrorResponse {
+    pub code: String,
+    pub message: String,
+}
+
+impl IntoResponse for QuizError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            QuizError::NotFound => StatusCode::NOT_FOUND,
+            QuizError::Forbidden => StatusCode::FORBIDDEN,
+            QuizError::QuizFinished
+            | QuizError::QuizPaused
+            | QuizError::QuizAbandoned
+            | QuizError::AlreadyAnswered
+            | QuizError::OutOfOrder
+            | QuizError::NotServed
+            | QuizError::StaleAttempt
+            | QuizError::TimeUp
+            | QuizError::PauseLimitReached
+            | QuizError::DailyChallengeTaken
+            | QuizError::ReviewInProgress
+            | QuizError::DailyChallengeClosed => StatusCode::CONFLICT,
+            QuizError::QuestionNotInQuiz | QuizError::BadRequest(_) => StatusCode::BAD_REQUEST,
+        };
+        let body = QuizErrorResponse { code: self.code().to_string(), message: self.to_string() };
+        (status, Json(body)).into_response()
+    }
+}
+
+fn caller(claims: &Claims) -> Result<Caller, QuizError> {
+    Caller::from_claims(claims).ok_or(QuizError::BadRequest("Invalid user ID".to_string()))
+}
+
+fn parse_id(id: &str, what: &str) -> Result<ObjectId, QuizError> {
+    ObjectId::parse_str(id).map_err(|_| QuizError::BadRequest(format!("Invalid {} ID", what)))
 }
 
 #[utoipa::path(
@@ -63,7 +162,7 @@ pub async fn get_leaderboard(
     request_body = StartQuizRequest,
     responses(
         (status = 201, description = "Quiz started successfully", body = StartQuizRequest),
-        (status = 400, description = "Invalid request or not enough questions"),
+        (status = 400, description = "Invalid request or not enough questions", body = QuizErrorResponse),
         (status = 401, description = "Unauthorized")
     ),
     security(("bearer_auth" = []))
@@ -72,21 +171,208 @@ pub async fn start_quiz(
     State((quiz_service, _user_service)): State<(Arc<QuizService>, Arc<UserService>)>,
     Extension(claims): Extension<Arc<Claims>>,    // 👈 this brings `claims` into scope
     Json(req): Json<StartQuizRequest>,
-) -> Result<(StatusCode, Json<Quiz>), (StatusCode, String)> {
-    let user_id = ObjectId::parse_str(&claims.sub)
-        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid user ID".to_string()))?;
+) -> Result<(StatusCode, Json<Quiz>), QuizError> {
+    let user_id = caller(&claims)?.user_id;
+    let category_id = parse_id(&req.category_id, "category")?;
 
-    let category_id = ObjectId::parse_str(&req.category_id)
-        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid category ID".to_string()))?;
+    let quiz = quiz_service
+        .start_quiz(user_id, category_id, req.difficulty, req.num_questions, req.sequential.unwrap_or(true), req.adaptive.unwrap_or(false))
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(quiz)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/quiz/exam/start",
+    request_body = StartExamRequest,
+    responses(
+        (status = 201, description = "Exam started; it is finished automatically at its deadline, unanswered questions counting as wrong", body = QuizResponse),
+        (status = 400, description = "Invalid request, time limit or not enough questions", body = QuizErrorResponse),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn start_exam(
+    State((quiz_service, _user_service)): State<(Arc<QuizService>, Arc<UserService>)>,
+    Extension(claims): Extension<Arc<Claims>>,
+    Json(req): Json<StartExamRequest>,
+) -> Result<(StatusCode, Json<Quiz>), QuizError> {
+    let user_id = caller(&claims)?.user_id;
+    let category_id = parse_id(&req.category_id, "category")?;
 
     let quiz = quiz_service
-        .start_quiz(user_id, category_id, req.difficulty, req.num_questions)
-        .await
-        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+        .start_exam(user_id, category_id, req.difficulty, req.num_questions, req.sequential.unwrap_or(false), req.time_limit_secs)
+        .await?;
 
     Ok((StatusCode::CREATED, Json(quiz)))
 }
 
+#[utoipa::path(
+    post,
+    path = "/quiz/compose",
+    request_body = ComposeQuizRequest,
+    responses(
+        (status = 201, description = "Quiz started successfully", body = QuizResponse),
+        (status = 400, description = "Invalid composition, unknown category or not enough questions", body = QuizErrorResponse),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn compose_quiz(
+    State((quiz_service, _user_service)): State<(Arc<QuizService>, Arc<UserService>)>,
+    Extension(claims): Extension<Arc<Claims>>,
+    Json(req): Json<ComposeQuizRequest>,
+) -> Result<(StatusCode, Json<Quiz>), QuizError> {
+    let user_id = caller(&claims)?.user_id;
+    let category_ids = req.category_ids
+        .iter()
+        .map(|id| parse_id(id, "category"))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let composition = QuizComposition {
+        category_ids,
+        include_subcategories: req.include_subcategories.unwrap_or(true),
+        tags: req.tags,
+        difficulty_mix: req.difficulty_mix,
+    };
+    let quiz = quiz_service
+        .compose_quiz(user_id, composition, req.num_questions, req.sequential.unwrap_or(true))
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(quiz)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/quiz/review/start",
+    request_body(content = Option<StartReviewRequest>, description = "Optional; may be omitted"),
+    responses(
+        (status = 201, description = "Review session started with the questions most overdue for review", body = QuizResponse),
+        (status = 400, description = "Invalid request, or no questions are due for review", body = QuizErrorResponse),
+        (status = 409, description = "`review_in_progress`: the caller has a review session open already", body = QuizErrorResponse),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn start_review(
+    State((quiz_service, _user_service)): State<(Arc<QuizService>, Arc<UserService>)>,
+    Extension(claims): Extension<Arc<Claims>>,
+    req: Option<Json<StartReviewRequest>>,
+) -> Result<(StatusCode, Json<Quiz>), QuizError> {
+    let user_id = caller(&claims)?.user_id;
+
+    let quiz = quiz_service
+        .start_review(user_id, req.and_then(|Json(req)| req.num_questions))
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(quiz)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/quiz/review/due",
+    responses(
+        (status = 200, description = "Number of questions due for review", body = DueReviewsResponse),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn due_reviews(
+    State((quiz_service, _user_service)): State<(Arc<QuizService>, Arc<UserService>)>,
+    Extension(claims): Extension<Arc<Claims>>,
+) -> Result<Json<DueReviewsResponse>, QuizError> {
+    let user_id = caller(&claims)?.user_id;
+
+    let due = quiz_service.due_reviews(user_id).await?;
+
+    Ok(Json(due))
+}
+
+#[utoipa::path(
+    get,
+    path = "/quiz/daily/{category_id}",
+    params(
+        ("category_id" = String, Path, description = "Category ID")
+    ),
+    responses(
+        (status = 200, description = "Today's challenge in the category", body = DailyChallengeResponse),
+        (status = 400, description = "Unknown category, or it has no questions", body = QuizErrorResponse),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_daily_challenge(
+    State((quiz_service, _user_service)): State<(Arc<QuizService>, Arc<UserService>)>,
+    Extension(claims): Extension<Arc<Claims>>,
+    Path(category_id): Path<String>,
+) -> Result<Json<DailyChallengeResponse>, QuizError> {
+    let user_id = caller(&claims)?.user_id;
+    let category_id = parse_id(&category_id, "category")?;
+
+    let challenge = quiz_service.daily_challenge(user_id, category_id).await?;
+
+    Ok(Json(challenge))
+}
+
+#[utoipa::path(
+    post,
+    path = "/quiz/daily/{category_id}/start",
+    params(
+        ("category_id" = String, Path, description = "Category ID")
+    ),
+    responses(
+        (status = 201, description = "Attempt at today's challenge started", body = QuizResponse),
+        (status = 400, description = "Unknown category, or it has no questions", body = QuizErrorResponse),
+        (status = 409, description = "`daily_challenge_taken`: the caller already attempted today's challenge", body = QuizErrorResponse),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn start_daily_challenge(
+    State((quiz_service, _user_service)): State<(Arc<QuizService>, Arc<UserService>)>,
+    Extension(claims): Extension<Arc<Claims>>,
+    Path(category_id): Path<String>,
+) -> Result<(StatusCode, Json<Quiz>), QuizError> {
+    let user_id = caller(&claims)?.user_id;
+    let category_id = parse_id(&category_id, "category")?;
+
+    let quiz = quiz_service.start_daily_challenge(user_id, category_id).await?;
+
+    Ok((StatusCode::CREATED, Json(quiz)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/quiz/daily/{category_id}/leaderboard",
+    params(
+        ("category_id" = String, Path, description = "Category ID"),
+        DailyLeaderboardQuery
+    ),
+    responses(
+        (status = 200, description = "Finished attempts at the day's challenge, best score first, then fastest", body = [DailyLeaderboardEntry]),
+        (status = 400, description = "Invalid category ID or date", body = QuizErrorResponse),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_daily_leaderboard(
+    State((quiz_service, _user_service)): State<(Arc<QuizService>, Arc<UserService>)>,
+    Path(category_id): Path<String>,
+    Query(query): Query<DailyLeaderboardQuery>,
+) -> Result<Json<Vec<DailyLeaderboardEntry>>, QuizError> {
+    let category_id = parse_id(&category_id, "category")?;
+    let date = match query.date {
+        Some(date) => NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|_| QuizError::BadRequest("Invalid date; expected YYYY-MM-DD".to_string()))?,
+        None => quiz_service.daily_challenge_service.today(),
+    };
+
+    let leaderboard = quiz_service.daily_challenge_service.leaderboard(category_id, date).await?;
+
+    Ok(Json(leaderboard))
+}
+
 #[utoipa::path(
     post,
     path = "/quiz/{id}/answer",
@@ -96,25 +382,26 @@ pub async fn start_quiz(
     request_body = SubmitAnswerRequest,
     responses(
         (status = 200, description = "Answer submitted successfully", body = SubmitAnswerRequest),
-        (status = 400, description = "Invalid request or quiz paused"),
-        (status = 401, description = "Unauthorized")
+        (status = 400, description = "Invalid request, or question not in this quiz (`question_not_in_quiz`)", body = QuizErrorResponse),
+        (status = 409, description = "`quiz_finished`, `quiz_paused`, `already_answered`, `out_of_order`, `question_not_served`, `stale_attempt`, `exam_time_up` or `daily_challenge_closed`", body = QuizErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Quiz belongs to another user", body = QuizErrorResponse),
+        (status = 404, description = "Quiz not found", body = QuizErrorResponse)
     ),
     security(("bearer_auth" = []))
 )]
 pub async fn submit_answer(
     State((quiz_service, _user_service)): State<(Arc<QuizService>, Arc<UserService>)>,
+    Extension(claims): Extension<Arc<Claims>>,
     Path(id): Path<String>,
     Json(req): Json<SubmitAnswerRequest>,
-) -> Result<(StatusCode, Json<Quiz>), (StatusCode, String)> {
-    let quiz_id = ObjectId::parse_str(&id)
-        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid quiz ID".to_string()))?;
-    let question_id = ObjectId::parse_str(&req.question_id)
-        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid question ID".to_string()))?;
+) -> Result<(StatusCode, Json<Quiz>), QuizError> {
+    let quiz_id = parse_id(&id, "quiz")?;
+    let question_id = parse_id(&req.question_id, "question")?;
 
     let quiz = quiz_service
-        .submit_answer(quiz_id, question_id, req.answer, req.time_taken)
-        .await
-        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+        .submit_answer(caller(&claims)?, quiz_id, question_id, req.answer, req.time_taken, req.version)
+        .await?;
 
     Ok((StatusCode::OK, Json(quiz)))
 }
@@ -125,27 +412,31 @@ pub async fn submit_answer(
     params(
         ("id" = String, Path, description = "Quiz ID")
     ),
+    request_body(content = Option<FinishQuizRequest>, description = "Optional; may be omitted"),
     responses(
-        (status = 200, description = "Quiz finished successfully", body = i32),
-        (status = 400, description = "Invalid request or quiz not found"),
-        (status = 401, description = "Unauthorized")
+        (status = 200, description = "Quiz finished successfully, or already finished", body = FinishQuizResponse),
+        (status = 400, description = "Invalid request", body = QuizErrorResponse),
+        (status = 409, description = "`stale_attempt`", body = QuizErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Quiz belongs to another user", body = QuizErrorResponse),
+        (status = 404, description = "Quiz not found", body = QuizErrorResponse)
     ),
     security(("bearer_auth" = []))
 )]
 pub async fn finish_quiz(
     State((quiz_service, user_service)): State<(Arc<QuizService>, Arc<UserService>)>,
-    Extension(_claims): Extension<Arc<Claims>>,
+    Extension(claims): Extension<Arc<Claims>>,
     Path(id): Path<String>,
-) -> Result<(StatusCode, Json<i32>), (StatusCode, String)> {
-    let quiz_id = ObjectId::parse_str(&id)
-        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid quiz ID".to_string()))?;
+    req: Option<Json<FinishQuizRequest>>,
+) -> Result<(StatusCode, Json<FinishQuizResponse>), QuizError> {
+    let quiz_id = parse_id(&id, "quiz")?;
+    let version = req.and_then(|Json(req)| req.version);
 
-    let score = quiz_service
-        .finish_quiz(&user_service, quiz_id)
-        .await
-        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let result = quiz_service
+        .finish_quiz(caller(&claims)?, &user_service, quiz_id, version)
+        .await?;
 
-    Ok((StatusCode::OK, Json(score)))
+    Ok((StatusCode::OK, Json(result)))
 }
 
 #[utoipa::path(
@@ -156,36 +447,139 @@ pub async fn finish_quiz(
     ),
     request_body = PauseQuizRequest,
     responses(
-        (status = 200, description = "Quiz paused/resumed successfully", body = Quiz),
-        (status = 400, description = "Invalid request or quiz not found"),
-        (status = 401, description = "Unauthorized")
+        (status = 200, description = "Quiz paused/resumed successfully", body = crate::models::quiz::QuizResponse),
+        (status = 400, description = "Invalid request, or the quiz is an exam", body = QuizErrorResponse),
+        (status = 409, description = "`quiz_finished`, `stale_attempt` or `pause_limit_reached`", body = QuizErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Quiz belongs to another user", body = QuizErrorResponse),
+        (status = 404, description = "Quiz not found", body = QuizErrorResponse)
     ),
     security(("bearer_auth" = []))
 )]
 pub async fn pause_quiz(
     State((quiz_service, _user_service)): State<(Arc<QuizService>, Arc<UserService>)>,
+    Extension(claims): Extension<Arc<Claims>>,
     Path(id): Path<String>,
     Json(req): Json<PauseQuizRequest>,
-) -> Result<(StatusCode, Json<Quiz>), (StatusCode, String)> {
-    let quiz_id = ObjectId::parse_str(&id)
-        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid quiz ID".to_string()))?;
+) -> Result<(StatusCode, Json<Quiz>), QuizError> {
+    let quiz_id = parse_id(&id, "quiz")?;
 
     let quiz = quiz_service
-        .pause_quiz(quiz_id, req.paused)
-        .await
-        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+        .pause_quiz(caller(&claims)?, quiz_id, req.paused, req.version)
+        .await?;
 
     Ok((StatusCode::OK, Json(quiz)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/quiz/{id}",
+    params(
+        ("id" = String, Path, description = "Quiz ID")
+    ),
+    responses(
+        (status = 200, description = "Quiz found", body = QuizResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Quiz belongs to another user (admins may read any quiz)", body = QuizErrorResponse),
+        (status = 404, description = "Quiz not found", body = QuizErrorResponse)
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_quiz(
+    State((quiz_service, _user_service)): State<(Arc<QuizService>, Arc<UserService>)>,
+    Extension(claims): Extension<Arc<Claims>>,
+    Path(id): Path<String>,
+) -> Result<Json<QuizResponse>, QuizError> {
+    let quiz_id = parse_id(&id, "quiz")?;
+
+    let quiz = quiz_service
+        .get_quiz(caller(&claims)?, quiz_id)
+        .await?;
+
+    Ok(Json(quiz.into()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/quiz/{id}/next",
+    params(
+        ("id" = String, Path, description = "Quiz ID")
+    ),
+    responses(
+        (status = 200, description = "Current question, without its answer; its timer starts on the first fetch", body = QuizQuestionResponse),
+        (status = 400, description = "Every question has been answered", body = QuizErrorResponse),
+        (status = 409, description = "`quiz_finished`, `quiz_paused`, `exam_time_up` or `daily_challenge_closed`", body = QuizErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Quiz belongs to another user", body = QuizErrorResponse),
+        (status = 404, description = "Quiz not found", body = QuizErrorResponse)
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn next_question(
+    State((quiz_service, _user_service)): State<(Arc<QuizService>, Arc<UserService>)>,
+    Extension(claims): Extension<Arc<Claims>>,
+    Path(id): Path<String>,
+) -> Result<Json<QuizQuestionResponse>, QuizError> {
+    let quiz_id = parse_id(&id, "quiz")?;
+
+    let question = quiz_service
+        .next_question(caller(&claims)?, quiz_id)
+        .await?;
+
+    Ok(Json(question))
+}
+
+#[utoipa::path(
+    get,
+    path = "/quiz/{id}/questions/{n}",
+    params(
+        ("id" = String, Path, description = "Quiz ID"),
+        ("n" = usize, Path, description = "Position of the question in the quiz, starting at 0")
+    ),
+    responses(
+        (status = 200, description = "The question; answered questions (all, once finished) include the correct answer and explanation", body = QuizQuestionResponse),
+        (status = 400, description = "No question at this position", body = QuizErrorResponse),
+        (status = 409, description = "`out_of_order` (sequential quiz, question not reached yet) or `quiz_paused`", body = QuizErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Quiz belongs to another user (admins may read any quiz)", body = QuizErrorResponse),
+        (status = 404, description = "Quiz not found", body = QuizErrorResponse)
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_quiz_question(
+    State((quiz_service, _user_service)): State<(Arc<QuizService>, Arc<UserService>)>,
+    Extension(claims): Extension<Arc<Claims>>,
+    Path((id, n)): Path<(String, usize)>,
+) -> Result<Json<QuizQuestionResponse>, QuizError> {
+    let quiz_id = parse_id(&id, "quiz")?;
+
+    let question = quiz_service
+        .question_at(caller(&claims)?, quiz_id, n)
+        .await?;
+
+    Ok(Json(question))
+}
+
 pub fn quiz_routes(quiz_service: Arc<QuizService>, user_service: Arc<UserService>) -> Router {
+    let token_service = user_service.token_service.clone();
+
     Router::new()
         .route("/quiz/start", axum::routing::post(start_quiz))
+        .route("/quiz/compose", axum::routing::post(compose_quiz))
+        .route("/quiz/exam/start", axum::routing::post(start_exam))
+        .route("/quiz/review/start", axum::routing::post(start_review))
+        .route("/quiz/review/due", axum::routing::get(due_reviews))
+        .route("/quiz/daily/{category_id}", axum::routing::get(get_daily_challenge))
+        .route("/quiz/daily/{category_id}/start", axum::routing::post(start_daily_challenge))
+        .route("/quiz/daily/{category_id}/leaderboard", axum::routing::get(get_daily_leaderboard))
         .route("/quiz/{id}/answer", axum::routing::post(submit_answer))
         .route("/quiz/{id}/finish", axum::routing::post(finish_quiz))
         .route("/quiz/{id}/pause", axum::routing::post(pause_quiz))
+        .route("/quiz/{id}", axum::routing::get(get_quiz))
+        .route("/quiz/{id}/next", axum::routing::get(next_question))
+        .route("/quiz/{id}/questions/{n}", axum::routing::get(get_quiz_question))
         .route("/quiz/leaderboard/{category_id}", axum::routing::get(get_leaderboard))
 
-    .layer(axum::middleware::from_fn(auth_middleware))
+    .layer(axum::middleware::from_fn_with_state(token_service, auth_middleware))
         .with_state((quiz_service, user_service))
 }
\ No newline at end of file
@@ -15,7 +15,8 @@ pub struct UpdateProfileRequest {
 #[utoipa::path(
     put,
     path = "/user/{id}/profile",
-    request_body = UpdateProfileRequest
+    request_body = UpdateProfileRequest,
+    security(("bearer_auth" = []))
 )]
 
 pub async fn update_profile(State(user_service): State<Arc<UserService>>, Path(id): Path<String>, Json(req): Json<UpdateProfileRequest>) -> Result<StatusCode, (StatusCode, String)> {
@@ -29,7 +30,8 @@ pub async fn update_profile(State(user_service): State<Arc<UserService>>, Path(i
     responses(
         (status = 200, description = "User found", body = UserResponse),
         (status = 404, description = "User not found")
-    )
+    ),
+    security(("bearer_auth" = []))
 )]
 pub async fn get_user(
     State(user_service): State<Arc<UserService>>, 
@@ -47,9 +49,11 @@ pub async fn get_user(
 
 
 pub fn user_routes(user_service: Arc<UserService>) -> Router {
+    let token_service = user_service.token_service.clone();
+
     Router::new()
         .route("/user/{id}/profile", axum::routing::put(update_profile))
         .route("/user/{id}", axum::routing::get(get_user))
-        .layer(middleware::from_fn(auth_middleware))
+        .layer(middleware::from_fn_with_state(token_service, auth_middleware))
         .with_state(user_service)
 }
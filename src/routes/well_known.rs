@@ -0,0 +1,27 @@
+use axum::{extract::State, http::{header, StatusCode}, response::IntoResponse, routing::get, Json, Router};
+use std::sync::Arc;
+
+use crate::models::jwt_key::Jwks;
+use crate::services::jwt_keys::JwtKeyStore;
+
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    responses(
+        (status = 200, description = "Public keys that verify access tokens, looked up by the token's `kid`", body = Jwks)
+    ),
+    security(())
+)]
+pub async fn jwks(
+    State(jwt_keys): State<Arc<JwtKeyStore>>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let jwks = jwt_keys.jwks().await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    // verifiers should still refetch when they meet an unknown kid
+    Ok(([(header::CACHE_CONTROL, "public, max-age=300")], Json(jwks)))
+}
+
+pub fn well_known_routes(jwt_keys: Arc<JwtKeyStore>) -> Router {
+    Router::new()
+        .route("/.well-known/jwks.json", get(jwks))
+        .with_state(jwt_keys)
+}
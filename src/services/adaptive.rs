@@ -0,0 +1,41 @@
+//! Elo-style ability estimation for adaptive quizzes. Each difficulty level stands for a fixed
+//! question rating; the player's rating moves after every answer by how much better or worse
+//! they did than the rating gap predicted, and the next question is drawn at the level closest
+//! to it, where the player is expected to get about half of the questions right.
+
+use crate::models::question::Difficulty;
+
+const LEVELS: [Difficulty; 4] = [Difficulty::Beginner, Difficulty::Intermediate, Difficulty::Advanced, Difficulty::Expert];
+
+/// How far the first answer can move the rating; later answers move it less, down to `MIN_K`,
+/// so that a quiz quickly finds the right level and then settles
+const START_K: f64 = 150.0;
+const MIN_K: f64 = 40.0;
+
+pub fn rating_of(difficulty: &Difficulty) -> f64 {
+    match difficulty {
+        Difficulty::Beginner => 1000.0,
+        Difficulty::Intermediate => 1200.0,
+        Difficulty::Advanced => 1400.0,
+        Difficulty::Expert => 1600.0,
+    }
+}
+
+/// Chance a player of `ability` answers a question of `difficulty` correctly
+pub fn expected_score(ability: f64, difficulty: &Difficulty) -> f64 {
+    1.0 / (1.0 + 10f64.powf((rating_of(difficulty) - ability) / 400.0))
+}
+
+/// The new ability after an answer earning `credit` (0.0-1.0) on a question of `difficulty`,
+/// with `answered` answers already given in the quiz
+pub fn update(ability: f64, difficulty: &Difficulty, credit: f64, answered: usize) -> f64 {
+    let k = (START_K / ((answered + 1) as f64).sqrt()).max(MIN_K);
+    ability + k * (credit - expected_score(ability, difficulty))
+}
+
+/// Difficulty levels from the best match for `ability` to the worst
+pub fn levels_for(ability: f64) -> Vec<Difficulty> {
+    let mut levels = LEVELS.to_vec();
+    levels.sort_by(|a, b| (rating_of(a) - ability).abs().total_cmp(&(rating_of(b) - ability).abs()));
+    levels
+}
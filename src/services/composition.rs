@@ -0,0 +1,38 @@
+use crate::models::{question::Difficulty, quiz::DifficultyShare};
+
+/// Split `num_questions` across a difficulty mix. Each level gets its percentage rounded down,
+/// and the questions left over go to the levels that lost the most in rounding, so the counts
+/// always add up to `num_questions`.
+pub fn allocate(mix: &[DifficultyShare], num_questions: usize) -> Result<Vec<(Difficulty, usize)>, String> {
+    if mix.is_empty() {
+        return Err("Difficulty mix must not be empty".to_string());
+    }
+    if mix.iter().map(|s| s.percent).sum::<u32>() != 100 {
+        return Err("Difficulty percentages must add up to 100".to_string());
+    }
+    if mix.iter().enumerate().any(|(i, s)| mix[..i].iter().any(|o| o.difficulty == s.difficulty)) {
+        return Err("Each difficulty may appear only once in the mix".to_string());
+    }
+
+    let mut counts: Vec<(Difficulty, usize, usize)> = mix
+        .iter()
+        .map(|s| {
+            let exact = num_questions * s.percent as usize;
+            (s.difficulty, exact / 100, exact % 100)
+        })
+        .collect();
+    let mut left = num_questions - counts.iter().map(|c| c.1).sum::<usize>();
+
+    let mut by_remainder: Vec<usize> = (0..counts.len()).collect();
+    // stable, so ties go to the level listed first
+    by_remainder.sort_by(|&a, &b| counts[b].2.cmp(&counts[a].2));
+    for i in by_remainder {
+        if left == 0 {
+            break;
+        }
+        counts[i].1 += 1;
+        left -= 1;
+    }
+
+    Ok(counts.into_iter().filter(|c| c.1 > 0).map(|c| (c.0, c.1)).collect())
+}
@@ -0,0 +1,63 @@
+//! Day boundaries and question selection for daily challenges. A challenge day runs from
+//! midnight to midnight in the configured time zone, and its questions are picked by a shuffle
+//! seeded from the category and the date, so regenerating a day's challenge always yields the
+//! same questions. Both the seed and the shuffle are implemented here rather than taken from
+//! `rand`, whose generators and shuffles may change between versions.
+
+use bson::oid::ObjectId;
+use chrono::{DateTime, Days, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// The challenge day `now` falls in
+pub fn challenge_day(now: DateTime<Utc>, tz: Tz) -> NaiveDate {
+    now.with_timezone(&tz).date_naive()
+}
+
+/// When `date` starts and ends in `tz`; days around daylight saving changes are 23 or 25 hours
+pub fn day_bounds(date: NaiveDate, tz: Tz) -> (DateTime<Utc>, DateTime<Utc>) {
+    let next = date.checked_add_days(Days::new(1)).unwrap_or(date);
+    (start_of_day(date, tz), start_of_day(next, tz))
+}
+
+/// Local midnight, or the first hour after it that exists where a clock change skips midnight
+fn start_of_day(date: NaiveDate, tz: Tz) -> DateTime<Utc> {
+    (0..24)
+        .filter_map(|hour| date.and_hms_opt(hour, 0, 0))
+        .find_map(|local| tz.from_local_datetime(&local).earliest())
+        .map(|start| start.with_timezone(&Utc))
+        .unwrap_or_else(|| date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc())
+}
+
+/// Seed for a category's challenge on `date` (FNV-1a, stable across builds and platforms)
+pub fn seed(category_id: ObjectId, date: NaiveDate) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let date = date.format("%Y-%m-%d").to_string();
+    for byte in category_id.bytes().iter().chain(date.as_bytes()) {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// Up to `count` of the `pool` questions, the same for the same pool and seed whatever order
+/// the pool was read in
+pub fn pick(mut pool: Vec<ObjectId>, count: usize, seed: u64) -> Vec<ObjectId> {
+    pool.sort();
+    // Fisher-Yates; the modulo bias is negligible for pools far below 2^64
+    let mut state = seed;
+    for i in (1..pool.len()).rev() {
+        let j = (splitmix64(&mut state) % (i as u64 + 1)) as usize;
+        pool.swap(i, j);
+    }
+    pool.truncate(count);
+    pool
+}
+
+/// Next value of a SplitMix64 sequence
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,153 @@
+use std::sync::Arc;
+
+use bson::{doc, oid::ObjectId, Document};
+use chrono::{NaiveDate, Utc};
+use chrono_tz::Tz;
+use futures::TryStreamExt;
+use mongodb::{Collection, Database};
+
+use crate::config::Config;
+use crate::models::{category::Category, daily_challenge::{DailyChallenge, DailyLeaderboardEntry}, question::Question, quiz::Quiz};
+use crate::services::daily;
+
+/// One shared question set per category and day, generated ahead of time by a scheduled job or,
+/// failing that, on first use.
+pub struct DailyChallengeService {
+    challenge_collection: Collection<DailyChallenge>,
+    category_collection: Collection<Category>,
+    question_collection: Collection<Question>,
+    quiz_collection: Collection<Quiz>,
+    timezone: Tz,
+    num_questions: usize,
+}
+
+impl DailyChallengeService {
+    pub fn new(db: Arc<Database>, config: &Config) -> Self {
+        Self {
+            challenge_collection: db.collection("daily_challenges"),
+            category_collection: db.collection("categories"),
+            question_collection: db.collection("questions"),
+            quiz_collection: db.collection("quizzes"),
+            timezone: config.daily_challenge_timezone,
+            num_questions: config.daily_challenge_questions,
+        }
+    }
+
+    /// The challenge day it is now in the configured time zone
+    pub fn today(&self) -> NaiveDate {
+        daily::challenge_day(Utc::now(), self.timezone)
+    }
+
+    /// The category's challenge for `date`, generating it if the scheduled job has not yet
+    pub async fn challenge_for(&self, category_id: ObjectId, date: NaiveDate) -> Result<DailyChallenge, String> {
+        match self.find(category_id, date).await? {
+            Some(challenge) => Ok(challenge),
+            None => self.generate(category_id, date).await,
+        }
+    }
+
+    pub async fn find(&self, category_id: ObjectId, date: NaiveDate) -> Result<Option<DailyChallenge>, String> {
+        self.challenge_collection
+            .find_one(doc! { "category_id": category_id, "date": date.format("%Y-%m-%d").to_string() })
+            .await
+            .map_err(|_| "Failed to fetch daily challenge".to_string())
+    }
+
+    /// Generate `date`'s challenge for every category that has questions; returns how many were
+    /// missing
+    pub async fn generate_all(&self, date: NaiveDate) -> Result<usize, String> {
+        let mut cursor = self.category_collection
+            .find(doc! {})
+            .await
+            .map_err(|e| e.to_string())?;
+        let mut created = 0;
+        while let Some(category) = cursor.try_next().await.map_err(|e| e.to_string())? {
+            let Some(category_id) = category.id else { continue };
+            if self.find(category_id, date).await?.is_some() {
+                continue;
+            }
+            let has_questions = self.question_collection
+                .count_documents(doc! { "category_id": category_id })
+                .limit(1)
+                .await
+                .map_err(|e| e.to_string())?;
+            if has_questions > 0 {
+                self.generate(category_id, date).await?;
+                created += 1;
+            }
+        }
+        Ok(created)
+    }
+
+    /// Pick the day's questions and store them unless another generator got there first; both
+    /// would have picked the same questions anyway.
+    async fn generate(&self, category_id: ObjectId, date: NaiveDate) -> Result<DailyChallenge, String> {
+        self.category_collection
+            .find_one(doc! { "_id": category_id })
+            .await
+            .map_err(|_| "Failed to fetch category".to_string())?
+            .ok_or("Category not found".to_string())?;
+
+        let mut cursor = self.question_collection
+            .clone_with_type::<Document>()
+            .find(doc! { "category_id": category_id })
+            .projection(doc! { "_id": 1 })
+            .await
+            .map_err(|_| "Failed to fetch questions".to_string())?;
+        let mut pool = vec![];
+        while let Some(question) = cursor.try_next().await.map_err(|_| "Error iterating questions".to_string())? {
+            pool.push(question.get_object_id("_id").map_err(|_| "Failed to parse question".to_string())?);
+        }
+        if pool.is_empty() {
+            return Err("Category has no questions for a daily challenge".to_string());
+        }
+
+        let (starts_at, ends_at) = daily::day_bounds(date, self.timezone);
+        let challenge = DailyChallenge {
+            id: None,
+            category_id,
+            date: date.format("%Y-%m-%d").to_string(),
+            timezone: self.timezone.name().to_string(),
+            questions: daily::pick(pool, self.num_questions, daily::seed(category_id, date)),
+            starts_at,
+            ends_at,
+            created_at: Utc::now(),
+        };
+        let fields = bson::to_document(&challenge).map_err(|e| e.to_string())?;
+        self.challenge_collection
+            .update_one(
+                doc! { "category_id": category_id, "date": &challenge.date },
+                doc! { "$setOnInsert": fields },
+            )
+            .upsert(true)
+            .await
+            .map_err(|_| "Failed to store daily challenge".to_string())?;
+
+        self.find(category_id, date)
+            .await?
+            .ok_or("Failed to store daily challenge".to_string())
+    }
+
+    /// Finished attempts at the category's challenge for `date`, best score first
+    pub async fn leaderboard(&self, category_id: ObjectId, date: NaiveDate) -> Result<Vec<DailyLeaderboardEntry>, String> {
+        let Some(challenge) = self.find(category_id, date).await? else {
+            return Ok(vec![]);
+        };
+        let mut cursor = self.quiz_collection
+            .find(doc! { "daily_challenge_id": challenge.id, "status": "Finished" })
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut entries = vec![];
+        while let Some(quiz) = cursor.try_next().await.map_err(|e| e.to_string())? {
+            // paused time is not playing time
+            let time_secs = quiz.end_time.map_or(0, |end| (end - quiz.start_time - quiz.paused_between(quiz.start_time, end)).num_seconds());
+            entries.push(DailyLeaderboardEntry { user_id: quiz.user_id, score: quiz.score, time_secs, rank: 0 });
+        }
+        entries.sort_by(|a, b| b.score.cmp(&a.score).then(a.time_secs.cmp(&b.time_secs)));
+        for (rank, entry) in entries.iter_mut().enumerate() {
+            entry.rank = rank as i32 + 1;
+        }
+        Ok(entries)
+    }
+}
@@ -0,0 +1,64 @@
+use crate::models::{question::{AnswerKey, MatchRules}, quiz::SubmittedAnswer};
+
+/// How much of `answer` is correct for `key`, from 0.0 to 1.0, comparing free text under
+/// `rules`. Only multi-select, ordering and fill-in-the-blank answers can be partly correct.
+/// An answer of the wrong shape for the question (a list for a numeric question, say) is an
+/// error rather than a wrong answer.
+pub fn grade(key: &AnswerKey, rules: &MatchRules, answer: &SubmittedAnswer) -> Result<f64, String> {
+    match (key, answer) {
+        (AnswerKey::Exact { answer: expected }, SubmittedAnswer::Text(given)) => {
+            let given_normalized = rules.normalize(given);
+            if std::iter::once(expected)
+                .chain(&rules.aliases)
+                .any(|accepted| rules.normalize(accepted) == given_normalized)
+            {
+                return Ok(1.0);
+            }
+            match &rules.pattern {
+                Some(pattern) => Ok(credit(rules.regex(pattern)?.is_match(&given_normalized))),
+                None => Ok(0.0),
+            }
+        }
+        (AnswerKey::MultiSelect { answers }, SubmittedAnswer::List(given)) => {
+            // each correct pick earns a share, each wrong pick costs one
+            let mut picked: Vec<&String> = given.iter().collect();
+            picked.sort();
+            picked.dedup();
+            let hits = picked.iter().filter(|&&p| answers.contains(p)).count();
+            let misses = picked.len() - hits;
+            Ok((hits.saturating_sub(misses)) as f64 / answers.len() as f64)
+        }
+        (AnswerKey::Ordering { order }, SubmittedAnswer::List(given)) => {
+            if given.len() != order.len() {
+                return Err(format!("Expected {} items, got {}", order.len(), given.len()));
+            }
+            let in_place = order.iter().zip(given).filter(|(expected, given)| expected == given).count();
+            Ok(in_place as f64 / order.len() as f64)
+        }
+        (AnswerKey::FillInTheBlank { blanks }, SubmittedAnswer::List(given)) => {
+            if given.len() != blanks.len() {
+                return Err(format!("Expected {} blanks, got {}", blanks.len(), given.len()));
+            }
+            let filled = blanks
+                .iter()
+                .zip(given)
+                .filter(|(accepted, given)| accepted.iter().any(|a| rules.normalize(a) == rules.normalize(given)))
+                .count();
+            Ok(filled as f64 / blanks.len() as f64)
+        }
+        (AnswerKey::Numeric { value, tolerance }, SubmittedAnswer::Number(given)) => {
+            Ok(credit((value - given).abs() <= *tolerance))
+        }
+        (AnswerKey::Numeric { value, tolerance }, SubmittedAnswer::Text(given)) => {
+            let given: f64 = given.trim().parse().map_err(|_| "Answer must be a number".to_string())?;
+            Ok(credit((value - given).abs() <= *tolerance))
+        }
+        (AnswerKey::Exact { .. }, _) => Err("Answer must be text".to_string()),
+        (AnswerKey::Numeric { .. }, _) => Err("Answer must be a number".to_string()),
+        _ => Err("Answer must be a list".to_string()),
+    }
+}
+
+fn credit(correct: bool) -> f64 {
+    if correct { 1.0 } else { 0.0 }
+}
@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use bson::{doc, oid::ObjectId};
+use chrono::{Duration, Utc};
+use mongodb::{Collection, Database};
+use rand::RngCore;
+
+use crate::config::Config;
+use crate::models::invite::{CreateInviteResponse, Invite};
+use crate::models::user::Role;
+use crate::utils::{hash_password, verify_password};
+
+fn invalid_invite() -> String {
+    "Invalid or expired invite code".to_string()
+}
+
+/// Admin-issued, single-use, expiring invites; the only way to register with an elevated role.
+pub struct InviteService {
+    collection: Collection<Invite>,
+    default_ttl: Duration,
+}
+
+impl InviteService {
+    pub fn new(db: Arc<Database>, config: &Config) -> Self {
+        Self {
+            collection: db.collection("invites"),
+            default_ttl: Duration::hours(config.invite_ttl_hours),
+        }
+    }
+
+    pub async fn create(
+        &self,
+        created_by: ObjectId,
+        role: Role,
+        expires_in_hours: Option<i64>,
+    ) -> Result<CreateInviteResponse, String> {
+        let ttl = match expires_in_hours {
+            Some(hours) if hours <= 0 => return Err("expires_in_hours must be positive".to_string()),
+            Some(hours) => Duration::hours(hours),
+            None => self.default_ttl,
+        };
+
+        let mut secret = [0u8; 24];
+        rand::rng().fill_bytes(&mut secret);
+        let secret = URL_SAFE_NO_PAD.encode(secret);
+
+        let now = Utc::now();
+        let invite = Invite {
+            id: Some(ObjectId::new()),
+            code_hash: hash_password(&secret),
+            role: role.clone(),
+            created_by,
+            created_at: now,
+            expires_at: now + ttl,
+            used_by: None,
+            used_at: None,
+        };
+        let invite_id = invite.id.unwrap();
+
+        self.collection
+            .insert_one(&invite)
+            .await
+            .map_err(|_| "Failed to create invite".to_string())?;
+
+        Ok(CreateInviteResponse {
+            id: invite_id.to_hex(),
+            // same `<record id>.<secret>` shape as refresh tokens
+            code: format!("{}.{}", invite_id.to_hex(), secret),
+            role,
+            expires_at: invite.expires_at,
+        })
+    }
+
+    /// The role an unused, unexpired invite would grant, without redeeming it.
+    pub async fn check(&self, code: &str) -> Result<Role, String> {
+        Ok(self.find_valid(code).await?.role)
+    }
+
+    /// Atomically mark the invite as used by `user_id` and return the role it grants.
+    pub async fn redeem(&self, code: &str, user_id: ObjectId) -> Result<Role, String> {
+        let invite = self.find_valid(code).await?;
+
+        // only the request that sets `used_by` wins, and only before expiry
+        let now = Utc::now();
+        let claimed = self.collection
+            .update_one(
+                doc! {
+                    "_id": invite.id,
+                    "used_by": null,
+                    "expires_at": { "$gt": bson::DateTime::from_chrono(now) },
+                },
+                doc! { "$set": { "used_by": user_id, "used_at": bson::DateTime::from_chrono(now) } },
+            )
+            .await
+            .map_err(|e| format!("DB query error: {}", e))?;
+
+        if claimed.modified_count == 1 { Ok(invite.role) } else { Err(invalid_invite()) }
+    }
+
+    /// The invite `code` stands for, if it can still be redeemed
+    async fn find_valid(&self, code: &str) -> Result<Invite, String> {
+        let (invite_id, secret) = code
+            .split_once('.')
+            .and_then(|(id, secret)| ObjectId::parse_str(id).ok().map(|oid| (oid, secret)))
+            .ok_or_else(invalid_invite)?;
+
+        let invite = self.collection
+            .find_one(doc! { "_id": invite_id })
+            .await
+            .map_err(|e| format!("DB query error: {}", e))?
+            .ok_or_else(invalid_invite)?;
+        if !verify_password(&invite.code_hash, secret) || invite.used_by.is_some() || invite.expires_at <= Utc::now() {
+            return Err(invalid_invite());
+        }
+        Ok(invite)
+    }
+
+    /// Give an invite back if registration failed after it was redeemed.
+    pub async fn release(&self, code: &str, user_id: ObjectId) -> Result<(), String> {
+        let Some(invite_id) = code.split_once('.').and_then(|(id, _)| ObjectId::parse_str(id).ok()) else {
+            return Ok(());
+        };
+        self.collection
+            .update_one(
+                doc! { "_id": invite_id, "used_by": user_id },
+                doc! { "$set": { "used_by": null, "used_at": null } },
+            )
+            .await
+            .map_err(|e| format!("DB query error: {}", e))?;
+        Ok(())
+    }
+
+    /// Revoke an unused invite. Returns false when there is no such unused invite.
+    pub async fn revoke(&self, invite_id: ObjectId) -> Result<bool, String> {
+        let result = self.collection
+            .delete_one(doc! { "_id": invite_id, "used_by": null })
+            .await
+            .map_err(|e| format!("DB query error: {}", e))?;
+        Ok(result.deleted_count == 1)
+    }
+}
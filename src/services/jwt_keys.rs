@@ -0,0 +1,180 @@
+use std::sync::{Arc, Mutex, RwLock};
+
+use base64::{engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD}, Engine};
+use bson::doc;
+use chrono::{DateTime, Duration, Utc};
+use futures::TryStreamExt;
+use jsonwebtoken::{decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use mongodb::{Collection, Database};
+use ring::{rand::SystemRandom, signature::{Ed25519KeyPair, KeyPair}};
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::models::jwt_key::{Jwk, Jwks, JwtKey};
+use crate::utils::Claims;
+
+/// How long the published key set is cached before re-reading the `jwt_keys` collection
+const CACHE_TTL_SECS: i64 = 60;
+/// Least time between reloads for tokens with an unknown `kid`, so forged ones cannot make
+/// every request read the collection
+const UNKNOWN_KID_RELOAD_SECS: i64 = 5;
+
+/// Signs and verifies access tokens with EdDSA keys kept in the `jwt_keys` collection.
+/// The newest key signs until it retires after the rotation interval; a new key is generated
+/// on demand. Retired keys stay published in the JWKS until the last token they signed expires.
+pub struct JwtKeyStore {
+    collection: Collection<JwtKey>,
+    issuer: String,
+    audience: String,
+    rotation: Duration,
+    access_ttl: Duration,
+    cache: RwLock<Option<(DateTime<Utc>, Vec<JwtKey>)>>,
+    unknown_kid_reload_at: Mutex<Option<DateTime<Utc>>>,
+    rotation_lock: tokio::sync::Mutex<()>,
+}
+
+impl JwtKeyStore {
+    pub fn new(db: Arc<Database>, config: &Config) -> Self {
+        Self {
+            collection: db.collection("jwt_keys"),
+            issuer: config.jwt_issuer.clone(),
+            audience: config.jwt_audience.clone(),
+            rotation: Duration::days(config.jwt_key_rotation_days),
+            access_ttl: Duration::seconds(config.access_token_ttl_secs),
+            cache: RwLock::new(None),
+            unknown_kid_reload_at: Mutex::new(None),
+            rotation_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Sign an access token with the current key.
+    pub async fn sign(&self, user_id: &str, role: &str, jti: &str, ttl: Duration) -> Result<String, String> {
+        let key = self.signing_key().await?;
+        let claims = Claims {
+            sub: user_id.to_string(),
+            role: role.to_string(),
+            exp: (Utc::now() + ttl).timestamp() as usize,
+            jti: jti.to_string(),
+            iss: self.issuer.clone(),
+            aud: self.audience.clone(),
+        };
+
+        let mut header = Header::new(Algorithm::EdDSA);
+        header.kid = Some(key.kid.clone());
+        let der = STANDARD.decode(&key.private_key).map_err(|_| "Corrupt signing key".to_string())?;
+        encode(&header, &claims, &EncodingKey::from_ed_der(&der)).map_err(|e| format!("Failed to sign token: {}", e))
+    }
+
+    /// Check signature, expiry, issuer and audience. `Ok(None)` means the token is not acceptable.
+    pub async fn verify(&self, token: &str) -> Result<Option<Claims>, String> {
+        let Some(kid) = decode_header(token).ok().and_then(|h| h.kid) else {
+            return Ok(None);
+        };
+
+        // A kid we have not seen may belong to a key another instance just generated
+        let mut key = self.keys(false).await?.into_iter().find(|k| k.kid == kid);
+        if key.is_none() && self.claim_unknown_kid_reload()? {
+            key = self.keys(true).await?.into_iter().find(|k| k.kid == kid);
+        }
+        let Some(key) = key else {
+            return Ok(None);
+        };
+
+        let decoding_key = DecodingKey::from_ed_components(&key.public_key).map_err(|_| "Corrupt verification key".to_string())?;
+        let mut validation = Validation::new(Algorithm::EdDSA);
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_audience(&[&self.audience]);
+        validation.set_required_spec_claims(&["exp", "sub", "iss", "aud"]);
+
+        Ok(decode::<Claims>(token, &decoding_key, &validation).ok().map(|data| data.claims))
+    }
+
+    /// Every key whose tokens may still be live, for `/.well-known/jwks.json`.
+    pub async fn jwks(&self) -> Result<Jwks, String> {
+        self.signing_key().await?;
+        let keys = self.keys(false).await?;
+        Ok(Jwks { keys: keys.iter().map(Jwk::from).collect() })
+    }
+
+    /// Generate a new key and start signing with it right away, ahead of the schedule.
+    pub async fn rotate(&self) -> Result<JwtKey, String> {
+        let _guard = self.rotation_lock.lock().await;
+        self.generate().await
+    }
+
+    async fn signing_key(&self) -> Result<JwtKey, String> {
+        let now = Utc::now();
+        if let Some(key) = self.keys(false).await?.into_iter().find(|k| k.retires_at > now) {
+            return Ok(key);
+        }
+
+        // Only one request per instance generates the replacement; others pick it up after the lock
+        let _guard = self.rotation_lock.lock().await;
+        if let Some(key) = self.keys(true).await?.into_iter().find(|k| k.retires_at > now) {
+            return Ok(key);
+        }
+        self.generate().await
+    }
+
+    async fn generate(&self) -> Result<JwtKey, String> {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+            .map_err(|_| "Failed to generate signing key".to_string())?;
+        let pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref())
+            .map_err(|_| "Failed to generate signing key".to_string())?;
+
+        let now = Utc::now();
+        let retires_at = now + self.rotation;
+        let key = JwtKey {
+            id: None,
+            kid: Uuid::new_v4().to_string(),
+            private_key: STANDARD.encode(pkcs8.as_ref()),
+            public_key: URL_SAFE_NO_PAD.encode(pair.public_key().as_ref()),
+            created_at: now,
+            retires_at,
+            // tokens signed just before retirement must stay verifiable until they expire
+            expires_at: retires_at + self.access_ttl,
+        };
+
+        self.collection
+            .insert_one(&key)
+            .await
+            .map_err(|_| "Failed to store signing key".to_string())?;
+        self.keys(true).await?;
+        Ok(key)
+    }
+
+    /// Whether a token with an unknown `kid` may reload the keys now; at most one such reload
+    /// per instance happens every `UNKNOWN_KID_RELOAD_SECS`.
+    fn claim_unknown_kid_reload(&self) -> Result<bool, String> {
+        let now = Utc::now();
+        let mut last = self.unknown_kid_reload_at.lock().map_err(|_| "Key reload lock poisoned".to_string())?;
+        if last.is_some_and(|at| now - at < Duration::seconds(UNKNOWN_KID_RELOAD_SECS)) {
+            return Ok(false);
+        }
+        *last = Some(now);
+        Ok(true)
+    }
+
+    /// Unexpired keys, newest first.
+    async fn keys(&self, reload: bool) -> Result<Vec<JwtKey>, String> {
+        let now = Utc::now();
+        if !reload {
+            let cache = self.cache.read().map_err(|_| "Key cache poisoned".to_string())?;
+            if let Some((_, keys)) = cache.as_ref().filter(|(loaded_at, _)| now - *loaded_at < Duration::seconds(CACHE_TTL_SECS)) {
+                return Ok(keys.iter().filter(|k| k.expires_at > now).cloned().collect());
+            }
+        }
+
+        let keys: Vec<JwtKey> = self.collection
+            .find(doc! { "expires_at": { "$gt": bson::DateTime::from_chrono(now) } })
+            .sort(doc! { "created_at": -1 })
+            .await
+            .map_err(|e| format!("DB query error: {}", e))?
+            .try_collect()
+            .await
+            .map_err(|e| format!("DB query error: {}", e))?;
+
+        *self.cache.write().map_err(|_| "Key cache poisoned".to_string())? = Some((now, keys.clone()));
+        Ok(keys)
+    }
+}
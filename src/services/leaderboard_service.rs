@@ -29,23 +29,15 @@ pub async fn get_leaderboard(&self, category_id: ObjectId) -> Result<Vec<Leaderb
     Ok(leaderboard)
 }
 
-    pub async fn update_leaderboard(&self, user_id: ObjectId, category_id: ObjectId, score: i32) -> Result<(), String> {
-        // Find existing entry for the user and category
+    /// Set a user's total score in a category and re-rank the category.
+    /// Takes the total rather than a delta so that repeating the call is harmless.
+    pub async fn set_score(&self, user_id: ObjectId, category_id: ObjectId, total: i32) -> Result<(), String> {
         let filter = doc! { "user_id": user_id, "category_id": category_id };
-        let existing_entry = self.collection.find_one(filter.clone(), ).await.map_err(|e| e.to_string())?;
-
-        let new_score = if let Some(entry) = existing_entry {
-            // Update existing score
-            entry.score + score
-        } else {
-            // New entry
-            score
-        };
 
         // Upsert the user's score
      self.collection.update_one(
     filter,
-    doc! { "$set": { "score": new_score, "user_id": user_id, "category_id": category_id } },
+    doc! { "$set": { "score": total, "user_id": user_id, "category_id": category_id } },
 )
 .upsert(true)
 .await
@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use bson::doc;
+use chrono::{DateTime, Duration, Utc};
+use mongodb::{options::ReturnDocument, Collection, Database};
+
+use crate::config::Config;
+use crate::models::login_attempt::LoginAttempts;
+
+/// Failures allowed before any backoff kicks in
+const FREE_FAILURES: i32 = 3;
+
+/// Tracks failed logins per account and per client IP. After a few free failures every further
+/// failure locks the key for an exponentially growing delay; reaching the limit locks it for the
+/// full lockout period.
+pub struct LoginThrottle {
+    collection: Collection<LoginAttempts>,
+    account_max_failures: i32,
+    ip_max_failures: i32,
+    backoff_base: Duration,
+    lockout: Duration,
+}
+
+pub fn account_key(phone_number: &str) -> String {
+    format!("account:{}", phone_number)
+}
+
+pub fn ip_key(ip: &str) -> String {
+    format!("ip:{}", ip)
+}
+
+impl LoginThrottle {
+    pub fn new(db: Arc<Database>, config: &Config) -> Self {
+        Self {
+            collection: db.collection("login_attempts"),
+            account_max_failures: config.login_max_failures,
+            ip_max_failures: config.login_ip_max_failures,
+            backoff_base: Duration::seconds(config.login_backoff_base_secs),
+            lockout: Duration::seconds(config.login_lockout_secs),
+        }
+    }
+
+    /// Returns the latest `locked_until` among the keys, if any of them is currently locked.
+    pub async fn locked_until(&self, keys: &[String]) -> Result<Option<DateTime<Utc>>, String> {
+        let now = Utc::now();
+        let mut latest: Option<DateTime<Utc>> = None;
+
+        for key in keys {
+            let entry = self.collection
+                .find_one(doc! { "key": key })
+                .await
+                .map_err(|e| format!("DB query error: {}", e))?;
+
+            if let Some(until) = entry.and_then(|e| e.locked_until).filter(|until| *until > now) {
+                latest = Some(latest.map_or(until, |l| l.max(until)));
+            }
+        }
+        Ok(latest)
+    }
+
+    pub async fn record_failure(&self, key: &str) -> Result<(), String> {
+        let now = Utc::now();
+        let entry = self.collection
+            .find_one_and_update(
+                doc! { "key": key },
+                doc! {
+                    "$inc": { "failures": 1 },
+                    "$set": { "last_failure_at": bson::DateTime::from_chrono(now) },
+                },
+            )
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .await
+            .map_err(|e| format!("DB query error: {}", e))?
+            .ok_or("Failed to record login failure".to_string())?;
+
+        let max_failures = if key.starts_with("ip:") { self.ip_max_failures } else { self.account_max_failures };
+        let delay = if entry.failures >= max_failures {
+            self.lockout
+        } else if entry.failures > FREE_FAILURES {
+            // 1x, 2x, 4x ... the base delay, never longer than a full lockout
+            let exponent = (entry.failures - FREE_FAILURES - 1).min(30) as u32;
+            (self.backoff_base * 2i32.saturating_pow(exponent)).min(self.lockout)
+        } else {
+            return Ok(());
+        };
+
+        self.collection
+            .update_one(
+                doc! { "key": key },
+                doc! { "$set": { "locked_until": bson::DateTime::from_chrono(now + delay) } },
+            )
+            .await
+            .map_err(|e| format!("DB query error: {}", e))?;
+        Ok(())
+    }
+
+    /// Forget all failures for a key (successful login or admin unlock).
+    pub async fn reset(&self, key: &str) -> Result<(), String> {
+        self.collection
+            .delete_one(doc! { "key": key })
+            .await
+            .map_err(|e| format!("DB query error: {}", e))?;
+        Ok(())
+    }
+}
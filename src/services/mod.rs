@@ -1,5 +1,18 @@
 pub mod user_service;
 pub mod question_service;
 pub mod quiz_service;
+pub mod grading;
+pub mod composition;
+pub mod adaptive;
+pub mod scoring;
+pub mod spaced_repetition;
+pub mod daily;
+pub mod daily_challenge_service;
 pub mod phone_verify;
-pub mod leaderboard_service;
\ No newline at end of file
+pub mod leaderboard_service;
+pub mod token_service;
+pub mod sms;
+pub mod mongo_otp;
+pub mod login_throttle;
+pub mod jwt_keys;
+pub mod invite_service;
\ No newline at end of file
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bson::doc;
+use chrono::{Duration, Utc};
+use mongodb::{Collection, Database};
+use rand::Rng;
+
+use crate::config::Config;
+use crate::models::otp::OtpCode;
+use crate::services::{phone_verify::OtpProvider, sms::SmsSender};
+use crate::utils::{hash_password, verify_password};
+
+/// Self-hosted OTP: codes are generated here, stored hashed in the TTL-indexed `otp_codes`
+/// collection and delivered through a pluggable `SmsSender`.
+pub struct MongoOtpProvider {
+    collection: Collection<OtpCode>,
+    sms: Arc<dyn SmsSender>,
+    ttl: Duration,
+    max_attempts: i32,
+    resend_cooldown: Duration,
+}
+
+impl MongoOtpProvider {
+    pub fn new(db: Arc<Database>, sms: Arc<dyn SmsSender>, config: &Config) -> Self {
+        Self {
+            collection: db.collection("otp_codes"),
+            sms,
+            ttl: Duration::seconds(config.otp_ttl_secs),
+            max_attempts: config.otp_max_attempts,
+            resend_cooldown: Duration::seconds(config.otp_resend_cooldown_secs),
+        }
+    }
+}
+
+#[async_trait]
+impl OtpProvider for MongoOtpProvider {
+    async fn send_verification(&self, phone_e164: &str) -> Result<(), String> {
+        let now = Utc::now();
+
+        let existing = self.collection
+            .find_one(doc! { "phone_number": phone_e164 })
+            .await
+            .map_err(|e| format!("DB query error: {}", e))?;
+
+        // Within the cooldown the code already sent stays valid and nothing new is sent. This
+        // succeeds like any other request: an error here would tell callers that someone, e.g. a
+        // password reset for a registered account, asked for a code for this phone.
+        if existing.is_some_and(|existing| !existing.consumed && existing.created_at + self.resend_cooldown > now) {
+            return Ok(());
+        }
+
+        let code = format!("{:06}", rand::rng().random_range(0..1_000_000));
+        let code_hash = hash_password(&code);
+        let record = OtpCode {
+            id: None,
+            phone_number: phone_e164.to_string(),
+            code_hash: code_hash.clone(),
+            attempts: 0,
+            consumed: false,
+            created_at: now,
+            expires_at: now + self.ttl,
+        };
+
+        // a new code always replaces the previous one for this phone
+        self.collection
+            .replace_one(doc! { "phone_number": phone_e164 }, record)
+            .upsert(true)
+            .await
+            .map_err(|_| "Failed to store verification code".to_string())?;
+
+        let body = format!(
+            "Your verification code is {}. It expires in {} minutes.",
+            code,
+            self.ttl.num_minutes().max(1)
+        );
+        if let Err(e) = self.sms.send_sms(phone_e164, &body).await {
+            // a code that never arrived must not hold back the retry with the cooldown
+            self.collection
+                .delete_one(doc! { "phone_number": phone_e164, "code_hash": &code_hash })
+                .await
+                .map_err(|e| format!("DB query error: {}", e))?;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    async fn check_verification(&self, phone_e164: &str, code: &str) -> Result<bool, String> {
+        // Count the attempt before comparing so parallel guesses cannot exceed the limit
+        let record = self.collection
+            .find_one_and_update(
+                doc! {
+                    "phone_number": phone_e164,
+                    "consumed": false,
+                    "expires_at": { "$gt": bson::DateTime::now() },
+                    "attempts": { "$lt": self.max_attempts },
+                },
+                doc! { "$inc": { "attempts": 1 } },
+            )
+            .await
+            .map_err(|e| format!("DB query error: {}", e))?;
+
+        let Some(record) = record else {
+            return Err("Verification code expired or too many attempts; request a new code".to_string());
+        };
+
+        if !verify_password(&record.code_hash, code) {
+            return Ok(false);
+        }
+
+        // single use: only the request that flips `consumed` wins
+        let result = self.collection
+            .update_one(
+                doc! { "_id": record.id, "consumed": false },
+                doc! { "$set": { "consumed": true } },
+            )
+            .await
+            .map_err(|e| format!("DB query error: {}", e))?;
+
+        Ok(result.modified_count == 1)
+    }
+}
@@ -1,10 +1,38 @@
-use std::env;
+use std::{collections::HashMap, env, sync::{Arc, Mutex}};
 
+use async_trait::async_trait;
+use mongodb::Database;
+use rand::Rng;
 use reqwest::Client;
 use serde_json::Value;
 
+use crate::config::{Config, OtpProviderKind, SmsSenderKind};
+use crate::services::{mongo_otp::MongoOtpProvider, sms::{LogSmsSender, SmsSender, TwilioSmsSender}};
 
 
+/// Sends one-time codes to a phone and checks them.
+/// `phone_e164` must be a normalized E.164 phone number like "+2348012345678".
+#[async_trait]
+pub trait OtpProvider: Send + Sync {
+    async fn send_verification(&self, phone_e164: &str) -> Result<(), String>;
+    async fn check_verification(&self, phone_e164: &str, code: &str) -> Result<bool, String>;
+}
+
+/// Build the OTP provider selected by `OTP_PROVIDER`.
+pub fn otp_provider_from_config(config: &Config, db: Arc<Database>) -> Result<Arc<dyn OtpProvider>, String> {
+    match config.otp_provider {
+        OtpProviderKind::Twilio => Ok(Arc::new(TwilioClient::new_from_env()?)),
+        OtpProviderKind::Local => Ok(Arc::new(LocalOtpProvider::new(config.local_otp_code.clone()))),
+        OtpProviderKind::Mongo => {
+            let sms: Arc<dyn SmsSender> = match config.sms_sender {
+                SmsSenderKind::Twilio => Arc::new(TwilioSmsSender::new_from_env()?),
+                SmsSenderKind::Log => Arc::new(LogSmsSender),
+            };
+            Ok(Arc::new(MongoOtpProvider::new(db, sms, config)))
+        }
+    }
+}
+
 
 #[derive(Clone)]
 pub struct TwilioClient{
@@ -17,25 +45,27 @@ pub struct TwilioClient{
 
 
 impl TwilioClient {
-    pub fn new_from_env() -> Self {
+    pub fn new_from_env() -> Result<Self, String> {
         let account_sid = env::var("TWILIO_ACCOUNT_SID")
-            .expect("TWILIO_ACCOUNT_SID must be set");
+            .map_err(|_| "TWILIO_ACCOUNT_SID must be set".to_string())?;
         let auth_token = env::var("TWILIO_AUTH_TOKEN")
-            .expect("TWILIO_AUTH_TOKEN must be set");
+            .map_err(|_| "TWILIO_AUTH_TOKEN must be set".to_string())?;
         let service_sid = env::var("TWILIO_VERIFY_SERVICE_SID")
-            .expect("TWILIO_VERIFY_SERVICE_SID must be set");
+            .map_err(|_| "TWILIO_VERIFY_SERVICE_SID must be set".to_string())?;
 
-        Self {
+        Ok(Self {
             client: Client::new(),
             account_sid,
             auth_token,
             service_sid,
-        }
+        })
     }
+}
 
+#[async_trait]
+impl OtpProvider for TwilioClient {
     /// Send verification (OTP) via Twilio Verify (SMS).
-    /// `phone_e164` must be a normalized E.164 phone number like "+2348012345678".
-    pub async fn send_verification(&self, phone_e164: &str) -> Result<(), String> {
+    async fn send_verification(&self, phone_e164: &str) -> Result<(), String> {
         let url = format!(
             "https://verify.twilio.com/v2/Services/{}/Verifications",
             self.service_sid
@@ -98,7 +128,7 @@ impl TwilioClient {
     //     Ok(false)
     // }
 
-    pub async fn check_verification(&self, phone_e164: &str, code: &str) -> Result<bool, String> {
+    async fn check_verification(&self, phone_e164: &str, code: &str) -> Result<bool, String> {
     println!("Checking SID={} To={} Code={}", self.service_sid, phone_e164, code);
 
     let url = format!(
@@ -134,3 +164,48 @@ impl TwilioClient {
 }
 
 }
+
+
+/// In-process provider for local development and tests: no SMS is sent.
+/// With a fixed code every verification uses it; otherwise a random code is generated and logged.
+pub struct LocalOtpProvider {
+    fixed_code: Option<String>,
+    pending: Mutex<HashMap<String, String>>,
+}
+
+impl LocalOtpProvider {
+    pub fn new(fixed_code: Option<String>) -> Self {
+        Self {
+            fixed_code,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl OtpProvider for LocalOtpProvider {
+    async fn send_verification(&self, phone_e164: &str) -> Result<(), String> {
+        let code = self
+            .fixed_code
+            .clone()
+            .unwrap_or_else(|| format!("{:06}", rand::rng().random_range(0..1_000_000)));
+        println!("[local otp] code for {} is {}", phone_e164, code);
+
+        self.pending
+            .lock()
+            .map_err(|_| "OTP store poisoned".to_string())?
+            .insert(phone_e164.to_string(), code);
+        Ok(())
+    }
+
+    async fn check_verification(&self, phone_e164: &str, code: &str) -> Result<bool, String> {
+        let mut pending = self.pending.lock().map_err(|_| "OTP store poisoned".to_string())?;
+
+        // codes are single use
+        if pending.get(phone_e164).map(String::as_str) == Some(code) {
+            pending.remove(phone_e164);
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
@@ -2,12 +2,13 @@
 
 
 use std::sync::Arc;
-use mongodb::{Collection, Database};
+use mongodb::{options::ReturnDocument, Collection, Database};
 use bson::{doc, oid::ObjectId};
 use futures::stream::TryStreamExt;
 use crate::models::{
     category::Category,
     question::Question,
+    scoring::ScoringPolicy,
 };
 
 pub struct QuestionService {
@@ -56,6 +57,17 @@ pub async fn list_categories(&self) -> Result<Vec<Category>, String> {
     }
     Ok(categories)
 }
+    /// Set or clear (back to the default) the scoring policy of a category's future quizzes
+    pub async fn set_scoring_policy(&self, id: ObjectId, policy: Option<ScoringPolicy>) -> Result<Category, String> {
+        let policy = bson::to_bson(&policy).map_err(|_| "Failed to serialize scoring policy".to_string())?;
+        self.category_collection
+            .find_one_and_update(doc! { "_id": id }, doc! { "$set": { "scoring_policy": policy } })
+            .return_document(ReturnDocument::After)
+            .await
+            .map_err(|_| "Failed to update category".to_string())?
+            .ok_or("Category not found".to_string())
+    }
+
     pub async fn delete_category(&self, id: ObjectId) -> Result<(), String> {
         let result = self.category_collection
             .delete_one(doc! { "_id": id })
@@ -1,29 +1,226 @@
 use std::sync::Arc;
 
-use bson::{doc, oid::ObjectId};
-use chrono::Utc;
+use bson::{doc, oid::ObjectId, Document};
+use chrono::{DateTime, Duration, Utc};
 use futures::TryStreamExt;
-use mongodb::{Collection, Database};
+use rand::seq::SliceRandom;
+use mongodb::{error::{ErrorKind, WriteFailure}, options::ReturnDocument, Collection, Database};
 use uuid::Uuid;
 
-use crate::{models::{category::{self, Category,  CategoryWithTopUserResponse}, question::{Difficulty, Question}, quiz::Quiz, user::UserResponse}, services::{leaderboard_service::LeaderboardService, user_service::UserService}};
+use crate::{models::{category::{self, Category,  CategoryWithTopUserResponse}, question::{AnswerKey, Difficulty, Question}, scoring::ScoringPolicy, ability::CategoryAbility, review::{DueReviewsResponse, ReviewState}, daily_challenge::DailyChallengeResponse, quiz::{AdaptiveState, DifficultyShare, ExamLimit, FinishQuizResponse, PauseInterval, QuizStatus, QuizComposition, QuizMode, QuestionReview, Quiz, QuizQuestionResponse, ServedQuestion, SubmittedAnswer, UserAnswer}, user::{Role, UserResponse}}, services::{adaptive, composition, grading, scoring, spaced_repetition::{self, Schedule}, daily_challenge_service::DailyChallengeService, leaderboard_service::LeaderboardService, user_service::UserService}, config::Config, utils::Claims};
 
+/// Who is acting on a quiz, taken from the access token
+#[derive(Debug, Clone, Copy)]
+pub struct Caller {
+    pub user_id: ObjectId,
+    pub is_admin: bool,
+}
+
+impl Caller {
+    pub fn from_claims(claims: &Claims) -> Option<Self> {
+        Some(Self {
+            user_id: ObjectId::parse_str(&claims.sub).ok()?,
+            is_admin: claims.role == Role::Admin.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum QuizError {
+    #[error("Quiz not found")]
+    NotFound,
+    /// The quiz belongs to someone else (admins may only read it)
+    #[error("You do not have access to this quiz")]
+    Forbidden,
+    #[error("Quiz is already finished")]
+    QuizFinished,
+    #[error("Quiz is paused")]
+    QuizPaused,
+    /// Left idle until the maintenance task closed it
+    #[error("Quiz was abandoned")]
+    QuizAbandoned,
+    #[error("Question is not part of this quiz")]
+    QuestionNotInQuiz,
+    #[error("Question has already been answered")]
+    AlreadyAnswered,
+    /// Sequential quizzes must be answered in the order the questions were drawn
+    #[error("Question is not the current question of this sequential quiz")]
+    OutOfOrder,
+    #[error("Question has not been served yet; fetch it first")]
+    NotServed,
+    /// The client acted on an outdated version of the quiz, or lost a race too many times
+    #[error("Quiz was changed by another request; reload it and try again")]
+    StaleAttempt,
+    /// The exam's deadline has passed; it is (or is about to be) finished automatically
+    #[error("Time is up for this exam")]
+    TimeUp,
+    /// The quiz's mode allows no more pauses, or no more paused time
+    #[error("No pauses left for this quiz")]
+    PauseLimitReached,
+    /// Each player gets one scored attempt per daily challenge
+    #[error("You have already attempted today's challenge")]
+    DailyChallengeTaken,
+    /// One review session at a time, so a question is not reviewed twice at once
+    #[error("You already have a review session in progress; finish it first")]
+    ReviewInProgress,
+    /// The daily challenge's day is over; answers given before then still count
+    #[error("This daily challenge has closed")]
+    DailyChallengeClosed,
+    #[error("{0}")]
+    BadRequest(String),
+}
+
+impl QuizError {
+    /// Stable, machine-readable code for API clients
+    pub fn code(&self) -> &'static str {
+        match self {
+            QuizError::NotFound => "quiz_not_found",
+            QuizError::Forbidden => "quiz_forbidden",
+            QuizError::QuizFinished => "quiz_finished",
+            QuizError::QuizPaused => "quiz_paused",
+            QuizError::QuizAbandoned => "quiz_abandoned",
+            QuizError::QuestionNotInQuiz => "question_not_in_quiz",
+            QuizError::AlreadyAnswered => "already_answered",
+            QuizError::OutOfOrder => "out_of_order",
+            QuizError::NotServed => "question_not_served",
+            QuizError::StaleAttempt => "stale_attempt",
+            QuizError::TimeUp => "exam_time_up",
+            QuizError::PauseLimitReached => "pause_limit_reached",
+            QuizError::DailyChallengeTaken => "daily_challenge_taken",
+            QuizError::ReviewInProgress => "review_in_progress",
+            QuizError::DailyChallengeClosed => "daily_challenge_closed",
+            QuizError::BadRequest(_) => "bad_request",
+        }
+    }
+}
+
+impl From<String> for QuizError {
+    fn from(e: String) -> Self {
+        QuizError::BadRequest(e)
+    }
+}
+
+/// Most categories a composed quiz can draw from
+const MAX_COMPOSED_CATEGORIES: usize = 20;
+
+/// How often a versioned write is retried against a freshly read quiz before giving up
+const MAX_WRITE_ATTEMPTS: usize = 5;
 
+/// Questions in a review session when the client does not ask for a number
+const DEFAULT_REVIEW_QUESTIONS: usize = 10;
+const MAX_REVIEW_QUESTIONS: usize = 50;
+
+/// Longest time limit an exam can have
+const MAX_EXAM_SECS: i64 = 24 * 60 * 60;
+
+fn single_category(category_id: ObjectId, difficulty: Difficulty) -> QuizComposition {
+    QuizComposition {
+        category_ids: vec![category_id],
+        include_subcategories: false,
+        tags: vec![],
+        difficulty_mix: vec![DifficultyShare { difficulty, percent: 100 }],
+    }
+}
+
+/// A quiz over `questions` that has just started, not yet stored; callers set whatever their
+/// mode does differently
+fn new_quiz(user_id: ObjectId, category_id: ObjectId, difficulty: Difficulty, questions: Vec<ObjectId>, scoring: ScoringPolicy) -> Quiz {
+    let now = Utc::now();
+    Quiz {
+        id: None,
+        uuid: Uuid::new_v4(),
+        user_id,
+        category_id,
+        difficulty,
+        questions,
+        answers: vec![],
+        served: vec![],
+        rewards_applied: false,
+        version: 0,
+        scoring,
+        adaptive: None,
+        composition: None,
+        mode: QuizMode::Standard,
+        daily_challenge_id: None,
+        challenge_ends_at: None,
+        exam: None,
+        sequential: false,
+        start_time: now,
+        end_time: None,
+        score: 0,
+        status: QuizStatus::InProgress,
+        abandoned_at: None,
+        last_activity_at: Some(now),
+        pauses: vec![],
+    }
+}
+
+/// Wrong, pointless answers for the questions of `quiz` that were never answered
+fn unanswered_as_wrong(quiz: &Quiz, end_time: DateTime<Utc>) -> Vec<UserAnswer> {
+    quiz.questions
+        .iter()
+        .filter(|&&question_id| quiz.answer_for(question_id).is_none())
+        .map(|&question_id| {
+            let served_at = quiz.served_at(question_id);
+            UserAnswer {
+                question_id,
+                answer: None,
+                time_taken: served_at.map_or(Duration::zero(), |at| end_time - at),
+                client_time_taken: None,
+                served_at,
+                answered_at: None,
+                correct: false,
+                late: false,
+                points: Some(0),
+            }
+        })
+        .collect()
+}
+
+/// `$set` fields resuming the quiz's open pause at `at`, if it has one
+fn close_open_pause(quiz: &Quiz, at: DateTime<Utc>) -> Document {
+    let mut set = doc! {};
+    if let Some(index) = quiz.pauses.iter().rposition(|p| p.resumed_at.is_none()) {
+        set.insert(format!("pauses.{}.resumed_at", index), bson::DateTime::from_chrono(at));
+    }
+    set
+}
+
+/// Reject changes to a quiz that is no longer being played
+fn ensure_open(quiz: &Quiz) -> Result<(), QuizError> {
+    match quiz.status {
+        QuizStatus::Finished => Err(QuizError::QuizFinished),
+        QuizStatus::Abandoned => Err(QuizError::QuizAbandoned),
+        QuizStatus::InProgress | QuizStatus::Paused => Ok(()),
+    }
+}
+
+fn is_duplicate_key(e: &mongodb::error::Error) -> bool {
+    matches!(&*e.kind, ErrorKind::Write(WriteFailure::WriteError(w)) if w.code == 11000)
+}
 
 pub struct QuizService {
     quiz_collection: Collection<Quiz>,
+    ability_collection: Collection<CategoryAbility>,
+    review_collection: Collection<ReviewState>,
     category_collection: Collection<Category>,
     question_collection: Collection<Question>,
-    pub leaderboard_service: Arc<LeaderboardService>
+    pub leaderboard_service: Arc<LeaderboardService>,
+    pub daily_challenge_service: DailyChallengeService,
+    answer_grace: Duration,
 }
 
 impl QuizService {
-    pub fn new(db: Arc<Database>, leaderboard_service:Arc<LeaderboardService>) -> Self {
+    pub fn new(db: Arc<Database>, leaderboard_service:Arc<LeaderboardService>, config: &Config) -> Self {
         Self {
             quiz_collection: db.collection("quizzes"),
+            ability_collection: db.collection("abilities"),
+            review_collection: db.collection("review_states"),
             category_collection: db.collection("categories"),
             question_collection: db.collection("questions"),
-            leaderboard_service
+            leaderboard_service,
+            daily_challenge_service: DailyChallengeService::new(db.clone(), config),
+            answer_grace: Duration::seconds(config.answer_grace_secs),
         }
     }
 
@@ -73,168 +270,947 @@ impl QuizService {
         category_id: ObjectId,
         difficulty: Difficulty,
         num_questions: i32,
+        sequential: bool,
+        adaptive: bool,
     ) -> Result<Quiz, String> {
+        if adaptive {
+            return self.start_adaptive_quiz(user_id, category_id, difficulty, num_questions).await;
+        }
 
+        self.compose_quiz(user_id, single_category(category_id, difficulty), num_questions, sequential).await
+    }
 
-    let  difficulty_str:&str = match difficulty {
-    Difficulty::Beginner => "Beginner",
-    Difficulty::Intermediate => "Intermediate",
-    Difficulty::Advanced => "Advanced",
-    Difficulty::Expert => "Expert",
-};
+    /// Start a quiz sat as an exam: it closes `time_limit_secs` after it starts, and is then
+    /// finished automatically with any unanswered questions counted as wrong.
+    pub async fn start_exam(
+        &self,
+        user_id: ObjectId,
+        category_id: ObjectId,
+        difficulty: Difficulty,
+        num_questions: i32,
+        sequential: bool,
+        time_limit_secs: i64,
+    ) -> Result<Quiz, String> {
+        if !(1..=MAX_EXAM_SECS).contains(&time_limit_secs) {
+            return Err(format!("Time limit must be between 1 and {} seconds", MAX_EXAM_SECS));
+        }
+        let mut quiz = self
+            .draw_composed_quiz(user_id, single_category(category_id, difficulty), num_questions, sequential)
+            .await?;
+        quiz.exam = Some(ExamLimit {
+            time_limit_secs,
+            deadline: quiz.start_time + Duration::seconds(time_limit_secs),
+        });
+        self.insert_quiz(quiz).await.map_err(|e| e.to_string())
+    }
 
-        let mut cursor = self.question_collection.aggregate(vec![
-            doc! { "$match": { "category_id": &category_id, "difficulty": difficulty_str } },
-            doc! { "$sample": { "size": num_questions } }
-        ], ).await.map_err(|_| "Failed to fetch questions".to_string())?;
+    /// Start a quiz drawing from several categories (and optionally their subcategories),
+    /// restricted to tags, with a given share of questions at each difficulty.
+    pub async fn compose_quiz(
+        &self,
+        user_id: ObjectId,
+        composition: QuizComposition,
+        num_questions: i32,
+        sequential: bool,
+    ) -> Result<Quiz, String> {
+        let quiz = self.draw_composed_quiz(user_id, composition, num_questions, sequential).await?;
+        self.insert_quiz(quiz).await.map_err(|e| e.to_string())
+    }
 
-        let mut questions = Vec::new();
-        while let Some(doc) = cursor.try_next().await.map_err(|_| "Error iterating questions".to_string())? {
-            let q: Question = bson::from_document(doc).map_err(|_| "Failed to parse question".to_string())?;
-            questions.push(q);
+    /// The questions of a composed quiz, drawn but not yet stored
+    async fn draw_composed_quiz(
+        &self,
+        user_id: ObjectId,
+        composition: QuizComposition,
+        num_questions: i32,
+        sequential: bool,
+    ) -> Result<Quiz, String> {
+        let num_questions = usize::try_from(num_questions)
+            .ok()
+            .filter(|&n| n > 0)
+            .ok_or("Number of questions must be positive".to_string())?;
+        let primary_category = *composition.category_ids.first().ok_or("At least one category is required".to_string())?;
+        if composition.category_ids.len() > MAX_COMPOSED_CATEGORIES {
+            return Err(format!("At most {} categories can be combined", MAX_COMPOSED_CATEGORIES));
         }
+        let allocation = composition::allocate(&composition.difficulty_mix, num_questions)?;
+        let category_ids = self.resolve_categories(&composition.category_ids, composition.include_subcategories).await?;
 
-        if questions.len() < num_questions as usize {
-            return Err("Not enough questions available".to_string());
+        let mut questions = Vec::with_capacity(num_questions);
+        for (difficulty, count) in allocation {
+            let level = bson::to_bson(&difficulty).map_err(|_| "Failed to serialize difficulty".to_string())?;
+            let mut filter = doc! { "category_id": { "$in": &category_ids }, "difficulty": level };
+            if !composition.tags.is_empty() {
+                filter.insert("tags", doc! { "$in": &composition.tags });
+            }
+            let mut cursor = self.question_collection.aggregate(vec![
+                doc! { "$match": filter },
+                doc! { "$sample": { "size": count as i64 } },
+                doc! { "$project": { "_id": 1 } },
+            ]).await.map_err(|_| "Failed to fetch questions".to_string())?;
+
+            let mut drawn = 0;
+            while let Some(doc) = cursor.try_next().await.map_err(|_| "Error iterating questions".to_string())? {
+                questions.push(doc.get_object_id("_id").map_err(|_| "Failed to parse question".to_string())?);
+                drawn += 1;
+            }
+            if drawn < count {
+                return Err(format!("Not enough {:?} questions available: need {}, found {}", difficulty, count, drawn));
+            }
         }
+        // mix the levels rather than serving them in blocks
+        questions.shuffle(&mut rand::rng());
 
-        let quiz = Quiz {
-            id: None,
-            uuid: Uuid::new_v4(),
-            user_id,
-            category_id,
-            difficulty,
-            questions: questions.iter().map(|q| q.id.clone().unwrap()).collect(),
-            answers: vec![],
-            start_time: Utc::now(),
-            end_time: None,
-            score: 0,
-            paused: false,
-        };
+        // the level with the largest share stands for the quiz as a whole
+        let difficulty = composition.difficulty_mix
+            .iter()
+            .max_by_key(|s| s.percent)
+            .map(|s| s.difficulty)
+            .unwrap_or(Difficulty::Beginner);
+
+        let scoring = self.category_scoring(primary_category).await?;
+        Ok(Quiz {
+            composition: Some(composition),
+            sequential,
+            ..new_quiz(user_id, primary_category, difficulty, questions, scoring)
+        })
+    }
 
+    /// Store a quiz built by one of the `start_*` methods and return it with its id. The unique
+    /// index on (user_id, daily_challenge_id) settles two simultaneous daily challenge starts.
+    async fn insert_quiz(&self, quiz: Quiz) -> Result<Quiz, QuizError> {
         let insert_result = self.quiz_collection
-            .insert_one(quiz.clone(), )
+            .insert_one(quiz.clone())
             .await
-            .map_err(|_| "Failed to start quiz".to_string())?;
+            .map_err(|e| if quiz.daily_challenge_id.is_some() && is_duplicate_key(&e) {
+                QuizError::DailyChallengeTaken
+            } else {
+                QuizError::BadRequest("Failed to start quiz".to_string())
+            })?;
 
         let mut quiz_with_id = quiz;
         quiz_with_id.id = insert_result.inserted_id.as_object_id();
-
         Ok(quiz_with_id)
     }
 
+    /// The given categories, which must all exist, plus with `include_subcategories` every
+    /// category below them.
+    async fn resolve_categories(&self, category_ids: &[ObjectId], include_subcategories: bool) -> Result<Vec<ObjectId>, String> {
+        let mut resolved: Vec<ObjectId> = vec![];
+        for id in category_ids {
+            if !resolved.contains(id) {
+                resolved.push(*id);
+            }
+        }
+        let found = self.category_collection
+            .count_documents(doc! { "_id": { "$in": &resolved } })
+            .await
+            .map_err(|_| "Failed to fetch categories".to_string())?;
+        if found < resolved.len() as u64 {
+            return Err("Category not found".to_string());
+        }
+
+        let mut frontier = resolved.clone();
+        while include_subcategories && !frontier.is_empty() {
+            let mut cursor = self.category_collection
+                .find(doc! { "parent_id": { "$in": &frontier }, "_id": { "$nin": &resolved } })
+                .await
+                .map_err(|_| "Failed to fetch categories".to_string())?;
+            frontier.clear();
+            while let Some(category) = cursor.try_next().await.map_err(|_| "Error iterating categories".to_string())? {
+                if let Some(id) = category.id {
+                    frontier.push(id);
+                    resolved.push(id);
+                }
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// Start an adaptive quiz of `num_questions`. Only the first question is drawn up front, at
+    /// the player's saved ability in the category or else at `difficulty`; each answer then
+    /// moves the ability estimate and draws the next question to match it.
+    async fn start_adaptive_quiz(
+        &self,
+        user_id: ObjectId,
+        category_id: ObjectId,
+        difficulty: Difficulty,
+        num_questions: i32,
+    ) -> Result<Quiz, String> {
+        let length = usize::try_from(num_questions).map_err(|_| "Invalid number of questions".to_string())?;
+        let available = self.question_collection
+            .count_documents(doc! { "category_id": category_id })
+            .await
+            .map_err(|_| "Failed to count questions".to_string())?;
+        if length == 0 || available < length as u64 {
+            return Err("Not enough questions available".to_string());
+        }
+
+        let ability = self.ability_collection
+            .find_one(doc! { "user_id": user_id, "category_id": category_id })
+            .await
+            .map_err(|_| "Failed to fetch ability".to_string())?
+            .map_or(adaptive::rating_of(&difficulty), |saved| saved.rating);
+        let first = self.draw_question(category_id, ability, &[])
+            .await?
+            .ok_or("Not enough questions available".to_string())?;
+
+        let scoring = self.category_scoring(category_id).await?;
+        let quiz = Quiz {
+            adaptive: Some(AdaptiveState { length, ability }),
+            // the next question depends on the previous answer
+            sequential: true,
+            ..new_quiz(user_id, category_id, difficulty, vec![first], scoring)
+        };
+        self.insert_quiz(quiz).await.map_err(|e| e.to_string())
+    }
+
+    /// Start a review session from the questions due in the user's review schedule, the most
+    /// overdue first
+    pub async fn start_review(&self, user_id: ObjectId, num_questions: Option<i32>) -> Result<Quiz, QuizError> {
+        let limit = match num_questions {
+            None => DEFAULT_REVIEW_QUESTIONS,
+            Some(n) => usize::try_from(n)
+                .ok()
+                .filter(|&n| n > 0)
+                .ok_or("Number of questions must be positive".to_string())?,
+        };
+        if limit > MAX_REVIEW_QUESTIONS {
+            return Err(QuizError::BadRequest(format!("At most {} questions can be reviewed at once", MAX_REVIEW_QUESTIONS)));
+        }
+
+        // the same due questions would be drawn again and rescheduled twice for one review
+        let open = self.quiz_collection
+            .count_documents(doc! { "user_id": user_id, "mode": "Review", "status": { "$in": ["InProgress", "Paused"] } })
+            .await
+            .map_err(|_| "Failed to fetch quiz".to_string())?;
+        if open > 0 {
+            return Err(QuizError::ReviewInProgress);
+        }
+
+        let now = bson::DateTime::from_chrono(Utc::now());
+        let mut cursor = self.review_collection
+            .find(doc! { "user_id": user_id, "due_at": { "$lte": now } })
+            .sort(doc! { "due_at": 1 })
+            .limit(limit as i64)
+            .await
+            .map_err(|_| "Failed to fetch review schedule".to_string())?;
+        let mut due = vec![];
+        while let Some(state) = cursor.try_next().await.map_err(|_| "Error iterating review schedule".to_string())? {
+            due.push(state.question_id);
+        }
+
+        // questions deleted since they were scheduled are left out
+        let mut found = self.question_collection
+            .find(doc! { "_id": { "$in": &due } })
+            .await
+            .map_err(|_| "Failed to fetch questions".to_string())?;
+        let mut existing = vec![];
+        while let Some(question) = found.try_next().await.map_err(|_| "Error iterating questions".to_string())? {
+            existing.push(question);
+        }
+        let questions: Vec<ObjectId> = due.into_iter().filter(|id| existing.iter().any(|q| q.id == Some(*id))).collect();
+        let first = questions
+            .first()
+            .and_then(|id| existing.iter().find(|q| q.id == Some(*id)))
+            .ok_or("No questions are due for review".to_string())?;
+
+        let scoring = self.category_scoring(first.category_id).await?;
+        let quiz = Quiz {
+            mode: QuizMode::Review,
+            sequential: true,
+            ..new_quiz(user_id, first.category_id, first.difficulty, questions, scoring)
+        };
+        self.insert_quiz(quiz).await
+    }
+
+    /// Today's challenge in the category, and the user's attempt at it if any
+    pub async fn daily_challenge(&self, user_id: ObjectId, category_id: ObjectId) -> Result<DailyChallengeResponse, String> {
+        let challenge = self.daily_challenge_service
+            .challenge_for(category_id, self.daily_challenge_service.today())
+            .await?;
+        let attempt = self.quiz_collection
+            .find_one(doc! { "user_id": user_id, "daily_challenge_id": challenge.id })
+            .await
+            .map_err(|_| "Failed to fetch quiz".to_string())?;
+        Ok(DailyChallengeResponse::from((challenge, attempt.and_then(|quiz| quiz.id))))
+    }
+
+    /// Start the user's one attempt at today's challenge in the category; everyone gets the
+    /// same questions in the same order
+    pub async fn start_daily_challenge(&self, user_id: ObjectId, category_id: ObjectId) -> Result<Quiz, QuizError> {
+        let challenge = self.daily_challenge_service
+            .challenge_for(category_id, self.daily_challenge_service.today())
+            .await?;
+        let attempted = self.quiz_collection
+            .count_documents(doc! { "user_id": user_id, "daily_challenge_id": challenge.id })
+            .await
+            .map_err(|_| "Failed to fetch quiz".to_string())?;
+        if attempted > 0 {
+            return Err(QuizError::DailyChallengeTaken);
+        }
+
+        let mut cursor = self.question_collection
+            .find(doc! { "_id": { "$in": &challenge.questions } })
+            .await
+            .map_err(|_| "Failed to fetch questions".to_string())?;
+        let mut levels: Vec<(Difficulty, usize)> = vec![];
+        while let Some(question) = cursor.try_next().await.map_err(|_| "Error iterating questions".to_string())? {
+            match levels.iter_mut().find(|(level, _)| *level == question.difficulty) {
+                Some((_, count)) => *count += 1,
+                None => levels.push((question.difficulty, 1)),
+            }
+        }
+        // the most common level stands for the quiz as a whole
+        let difficulty = levels
+            .into_iter()
+            .max_by_key(|&(_, count)| count)
+            .map_or(Difficulty::Beginner, |(level, _)| level);
+
+        let scoring = self.category_scoring(category_id).await?;
+        let quiz = Quiz {
+            mode: QuizMode::Daily,
+            daily_challenge_id: challenge.id,
+            challenge_ends_at: Some(challenge.ends_at),
+            sequential: true,
+            ..new_quiz(user_id, category_id, difficulty, challenge.questions, scoring)
+        };
+        self.insert_quiz(quiz).await
+    }
+
+    /// How many of the user's scheduled questions are due, and when the next one will be
+    pub async fn due_reviews(&self, user_id: ObjectId) -> Result<DueReviewsResponse, String> {
+        let now = bson::DateTime::from_chrono(Utc::now());
+        let due = self.review_collection
+            .count_documents(doc! { "user_id": user_id, "due_at": { "$lte": now } })
+            .await
+            .map_err(|_| "Failed to count due reviews".to_string())?;
+        let scheduled = self.review_collection
+            .count_documents(doc! { "user_id": user_id })
+            .await
+            .map_err(|_| "Failed to count scheduled reviews".to_string())?;
+        let next = self.review_collection
+            .find_one(doc! { "user_id": user_id, "due_at": { "$gt": now } })
+            .sort(doc! { "due_at": 1 })
+            .await
+            .map_err(|_| "Failed to fetch review schedule".to_string())?;
+
+        Ok(DueReviewsResponse {
+            due,
+            scheduled,
+            next_due_at: next.map(|state| state.due_at.to_rfc3339()),
+        })
+    }
+
+    async fn category_scoring(&self, category_id: ObjectId) -> Result<ScoringPolicy, String> {
+        Ok(self.category_collection
+            .find_one(doc! { "_id": category_id })
+            .await
+            .map_err(|_| "Failed to fetch category".to_string())?
+            .and_then(|category| category.scoring_policy)
+            .unwrap_or_default())
+    }
+
+    /// A random question of the category not in `exclude`, at the difficulty closest to `ability`
+    /// that still has one left.
+    async fn draw_question(&self, category_id: ObjectId, ability: f64, exclude: &[ObjectId]) -> Result<Option<ObjectId>, String> {
+        for level in adaptive::levels_for(ability) {
+            let level = bson::to_bson(&level).map_err(|_| "Failed to serialize difficulty".to_string())?;
+            let mut cursor = self.question_collection
+                .aggregate(vec![
+                    doc! { "$match": { "category_id": category_id, "difficulty": level, "_id": { "$nin": exclude } } },
+                    doc! { "$sample": { "size": 1 } },
+                    doc! { "$project": { "_id": 1 } },
+                ])
+                .await
+                .map_err(|_| "Failed to fetch questions".to_string())?;
+            if let Some(doc) = cursor.try_next().await.map_err(|_| "Error iterating questions".to_string())? {
+                return Ok(doc.get_object_id("_id").ok());
+            }
+        }
+        Ok(None)
+    }
+
+    /// Load a quiz the caller may read: their own, or any quiz for admins.
+    pub async fn get_quiz(&self, caller: Caller, quiz_id: ObjectId) -> Result<Quiz, QuizError> {
+        let quiz = self.find_quiz(quiz_id).await?;
+        if quiz.user_id != caller.user_id && !caller.is_admin {
+            return Err(QuizError::Forbidden);
+        }
+        Ok(quiz)
+    }
+
+    /// Load a quiz the caller may change. Only the owner may; the admin override is read-only.
+    async fn owned_quiz(&self, caller: Caller, quiz_id: ObjectId) -> Result<Quiz, QuizError> {
+        let quiz = self.find_quiz(quiz_id).await?;
+        if quiz.user_id != caller.user_id {
+            return Err(QuizError::Forbidden);
+        }
+        Ok(quiz)
+    }
+
+    async fn find_quiz(&self, quiz_id: ObjectId) -> Result<Quiz, QuizError> {
+        self.quiz_collection
+            .find_one(doc! { "_id": quiz_id }, )
+            .await
+            .map_err(|_| QuizError::BadRequest("Failed to fetch quiz".to_string()))?
+            .ok_or(QuizError::NotFound)
+    }
+
+    /// Load the caller's quiz for a change, rejecting it if the client saw another version.
+    async fn quiz_for_write(&self, caller: Caller, quiz_id: ObjectId, expected_version: Option<i64>) -> Result<Quiz, QuizError> {
+        let quiz = self.owned_quiz(caller, quiz_id).await?;
+        if expected_version.is_some_and(|v| v != quiz.version) {
+            return Err(QuizError::StaleAttempt);
+        }
+        Ok(quiz)
+    }
+
+    /// Apply `update` only if the quiz is still at the version it was read at, bumping it.
+    /// `None` means another write got there first.
+    async fn write_versioned(&self, quiz: &Quiz, mut update: Document) -> Result<Option<Quiz>, QuizError> {
+        // quizzes created before versioning have no field yet
+        let version = if quiz.version == 0 { doc! { "$in": [0, null] } } else { doc! { "$eq": quiz.version } };
+        // merged into, not over, an `$inc` the caller already has (the answer's score)
+        match update.get_document_mut("$inc") {
+            Ok(inc) => { inc.insert("version", 1); }
+            Err(_) => { update.insert("$inc", doc! { "version": 1 }); }
+        }
+
+        let updated = self.quiz_collection
+            .find_one_and_update(doc! { "_id": quiz.id, "version": version }, update)
+            .return_document(ReturnDocument::After)
+            .await
+            .map_err(|_| "Failed to update quiz".to_string())?;
+        Ok(updated)
+    }
+
     pub async fn submit_answer(
         &self,
+        caller: Caller,
         quiz_id: ObjectId,
         question_id: ObjectId,
-        answer: String,
-        time_taken: i64,
-    ) -> Result<Quiz, String> {
-        let mut quiz = self.quiz_collection
-            .find_one(doc! { "_id": &quiz_id }, )
-            .await
-            .map_err(|_| "Failed to fetch quiz".to_string())?
-            .ok_or("Quiz not found".to_string())?;
+        answer: SubmittedAnswer,
+        client_time_taken: Option<i64>,
+        expected_version: Option<i64>,
+    ) -> Result<Quiz, QuizError> {
+        // taken once, so that losing a race does not make the answer any later
+        let answered_at = Utc::now();
+
+        for _ in 0..MAX_WRITE_ATTEMPTS {
+            let quiz = self.quiz_for_write(caller, quiz_id, expected_version).await?;
+
+            ensure_open(&quiz)?;
+            if quiz.status == QuizStatus::Paused {
+                return Err(QuizError::QuizPaused);
+            }
+            if quiz.exam.as_ref().is_some_and(|exam| answered_at > exam.deadline + self.answer_grace) {
+                return Err(QuizError::TimeUp);
+            }
+            if quiz.challenge_ends_at.is_some_and(|ends_at| answered_at > ends_at + self.answer_grace) {
+                return Err(QuizError::DailyChallengeClosed);
+            }
+            if !quiz.questions.contains(&question_id) {
+                return Err(QuizError::QuestionNotInQuiz);
+            }
+            if quiz.answer_for(question_id).is_some() {
+                return Err(QuizError::AlreadyAnswered);
+            }
+            if quiz.sequential && quiz.current_question() != Some(question_id) {
+                return Err(QuizError::OutOfOrder);
+            }
+            let served_at = quiz.served_at(question_id).ok_or(QuizError::NotServed)?;
 
-        if quiz.paused {
-            return Err("Cannot submit answer to a paused quiz".to_string());
+            let question = self.question_collection
+                .find_one(doc! { "_id": &question_id }, )
+                .await
+                .map_err(|_| "Failed to fetch question".to_string())?
+                .ok_or("Question not found".to_string())?;
+
+            // time spent paused does not count against the question's timer
+            let time_taken = answered_at - served_at - quiz.paused_between(served_at, answered_at);
+            let late = time_taken > question.timer + self.answer_grace;
+
+            let credit = grading::grade(&question.answer_key(), &question.match_rules(), &answer)?;
+            let streak = quiz.answers.iter().rev().take_while(|a| a.correct).count();
+            let scored = scoring::score(&quiz.scoring, &question.difficulty, credit, time_taken, question.timer, late, streak);
+            let points = scored.points;
+
+            let user_answer = UserAnswer {
+                question_id,
+                answer: Some(answer.clone()),
+                time_taken,
+                client_time_taken: client_time_taken.map(Duration::seconds),
+                served_at: Some(served_at),
+                answered_at: Some(answered_at),
+                correct: scored.correct,
+                late,
+                points: Some(points),
+            };
+            let answer_bson = bson::to_bson(&user_answer).map_err(|_| "Failed to serialize answer".to_string())?;
+
+            let mut push = doc! { "answers": answer_bson };
+            let mut set = doc! { "last_activity_at": bson::DateTime::from_chrono(answered_at) };
+            if let Some(state) = &quiz.adaptive {
+                let credit = if late { 0.0 } else { credit };
+                let ability = adaptive::update(state.ability, &question.difficulty, credit, quiz.answers.len());
+                set.insert("adaptive.ability", ability);
+                if quiz.answers.len() + 1 < state.length {
+                    match self.draw_question(quiz.category_id, ability, &quiz.questions).await? {
+                        Some(next) => { push.insert("questions", next); }
+                        // the category ran out of questions: end the quiz here
+                        None => { set.insert("adaptive.length", quiz.questions.len() as i64); }
+                    }
+                }
+            }
+
+            let update = doc! { "$push": push, "$inc": { "score": points }, "$set": set };
+            if let Some(quiz) = self.write_versioned(&quiz, update).await? {
+                return Ok(quiz);
+            }
+            // another write got in between: re-check against the quiz as it is now
+        }
+        Err(QuizError::StaleAttempt)
+    }
+
+    /// Serve the quiz's next unanswered question, starting its timer on the first fetch.
+    pub async fn next_question(&self, caller: Caller, quiz_id: ObjectId) -> Result<QuizQuestionResponse, QuizError> {
+        let quiz = self.owned_quiz(caller, quiz_id).await?;
+        ensure_open(&quiz)?;
+        let current = quiz.current_question().ok_or(QuizError::BadRequest("No more questions in this quiz".to_string()))?;
+        let index = quiz.questions.iter().position(|&q| q == current).unwrap_or_default();
+        self.question_at(caller, quiz_id, index).await
+    }
+
+    /// Question `index` of a quiz. Answered questions (or all of them, once the quiz is finished)
+    /// come with their review. Unanswered ones are served to the owner, only the current one
+    /// when the quiz is sequential; admins reading someone else's quiz never start a timer.
+    pub async fn question_at(&self, caller: Caller, quiz_id: ObjectId, index: usize) -> Result<QuizQuestionResponse, QuizError> {
+        let mut quiz = self.get_quiz(caller, quiz_id).await?;
+        let question_id = *quiz.questions
+            .get(index)
+            .ok_or(QuizError::BadRequest(format!("Quiz has no question at position {}", index)))?;
+
+        let finished = quiz.status.is_closed();
+        let unanswered = quiz.answer_for(question_id).is_none();
+        if !finished && unanswered && quiz.sequential && quiz.current_question() != Some(question_id) {
+            return Err(QuizError::OutOfOrder);
+        }
+        // the player cannot study an open question while its clock is stopped
+        if !finished && unanswered && quiz.status == QuizStatus::Paused && quiz.user_id == caller.user_id {
+            return Err(QuizError::QuizPaused);
+        }
+
+        if !finished && unanswered && quiz.served_at(question_id).is_none() && quiz.user_id == caller.user_id {
+            if quiz.exam.as_ref().is_some_and(|exam| Utc::now() >= exam.deadline) {
+                return Err(QuizError::TimeUp);
+            }
+            if quiz.challenge_ends_at.is_some_and(|ends_at| Utc::now() >= ends_at) {
+                return Err(QuizError::DailyChallengeClosed);
+            }
+            let served = ServedQuestion { question_id, served_at: Utc::now() };
+            let served_bson = bson::to_bson(&served).map_err(|_| "Failed to serialize served question".to_string())?;
+            // Conditional so concurrent fetches serve the question only once, and never into a
+            // quiz that was paused or finished meanwhile. It does not bump the version: starting
+            // a timer cannot invalidate what the client last saw.
+            self.quiz_collection
+                .update_one(
+                    doc! { "_id": quiz_id, "status": "InProgress", "served.question_id": { "$ne": question_id } },
+                    doc! {
+                        "$push": { "served": served_bson },
+                        "$set": { "last_activity_at": bson::DateTime::from_chrono(served.served_at) },
+                    },
+                )
+                .await
+                .map_err(|_| "Failed to serve question".to_string())?;
+            quiz = self.find_quiz(quiz_id).await?;
         }
 
         let question = self.question_collection
-            .find_one(doc! { "_id": &question_id }, )
+            .find_one(doc! { "_id": question_id })
             .await
             .map_err(|_| "Failed to fetch question".to_string())?
             .ok_or("Question not found".to_string())?;
 
-        let correct = question.correct_answer == answer;
-        let base_points = match question.difficulty {
-            Difficulty::Beginner => 5,
-            Difficulty::Intermediate => 10,
-            Difficulty::Advanced => 20,
-            Difficulty::Expert => 30,
-        };
-        let time_bonus = if time_taken < question.timer.num_seconds() / 2 { 10 } else { 0 };
-        let points = if correct { base_points + time_bonus } else { 0 };
-
-        quiz.score += points;
-        quiz.answers.push(crate::models::quiz::UserAnswer {
-            question_id,
-            answer,
-            time_taken: chrono::Duration::seconds(time_taken),
-            correct,
+        let answer = quiz.answer_for(question_id);
+        let review = (answer.is_some() || finished).then(|| QuestionReview {
+            your_answer: answer.and_then(|a| a.answer.clone()),
+            correct: answer.is_some_and(|a| a.correct),
+            late: answer.is_some_and(|a| a.late),
+            correct_answer: question.correct_answer.clone(),
+            explanation: question.explanation.clone(),
         });
 
-        let anser_bson = bson::to_bson(&quiz.answers).map_err(|_| "Failed to serialize answers".to_string())?;
+        let blanks = match question.answer_key() {
+            AnswerKey::FillInTheBlank { blanks } => Some(blanks.len()),
+            _ => None,
+        };
+
+        Ok(QuizQuestionResponse {
+            quiz_id: quiz_id.to_hex(),
+            index,
+            total: quiz.total_questions(),
+            question_id: question_id.to_hex(),
+            text: question.question,
+            question_type: question.question_type,
+            blanks,
+            options: question.options,
+            difficulty: question.difficulty,
+            timer_secs: question.timer.num_seconds(),
+            served_at: quiz.served_at(question_id).map(|at| at.to_rfc3339()),
+            review,
+        })
+    }
 
-        self.quiz_collection.update_one(
-            doc! { "_id": &quiz_id },
-            doc! { "$set": { "answers": anser_bson, "score": quiz.score } },
-            
-        ).await.map_err(|_| "Failed to update quiz".to_string())?;
+    pub async fn pause_quiz(&self, caller: Caller, quiz_id: ObjectId, paused: bool, expected_version: Option<i64>) -> Result<Quiz, QuizError> {
+        for _ in 0..MAX_WRITE_ATTEMPTS {
+            let quiz = self.quiz_for_write(caller, quiz_id, expected_version).await?;
+            ensure_open(&quiz)?;
+            if (quiz.status == QuizStatus::Paused) == paused {
+                return Ok(quiz);
+            }
+            // an exam's clock keeps running, so pausing would only hide the questions
+            if quiz.exam.is_some() {
+                return Err(QuizError::BadRequest("Exams cannot be paused".to_string()));
+            }
 
-        Ok(quiz)
+            let now = Utc::now();
+            let update = if paused {
+                let limits = quiz.mode.pause_limits();
+                let used = quiz.paused_between(quiz.start_time, now);
+                if quiz.pauses.len() >= limits.max_pauses || used >= limits.max_total {
+                    return Err(QuizError::PauseLimitReached);
+                }
+                let pause = PauseInterval {
+                    question_id: quiz.question_on_screen(),
+                    paused_at: now,
+                    resumed_at: None,
+                    allowance_ends_at: now + (limits.max_total - used),
+                };
+                let pause = bson::to_bson(&pause).map_err(|_| "Failed to serialize pause".to_string())?;
+                doc! { "$set": { "status": "Paused", "last_activity_at": bson::DateTime::from_chrono(now) }, "$push": { "pauses": pause } }
+            } else {
+                let mut set = doc! { "status": "InProgress", "last_activity_at": bson::DateTime::from_chrono(now) };
+                set.extend(close_open_pause(&quiz, now));
+                doc! { "$set": set }
+            };
+
+            if let Some(quiz) = self.write_versioned(&quiz, update).await? {
+                return Ok(quiz);
+            }
+        }
+        Err(QuizError::StaleAttempt)
     }
 
-    pub async fn pause_quiz(&self, quiz_id: ObjectId, paused: bool) -> Result<Quiz, String> {
-        let update = doc! { "$set": { "paused": paused } };
-        self.quiz_collection
-            .find_one_and_update(doc! { "_id": quiz_id }, update, )
+    /// Finish a quiz. Only the first call ends it; repeating it returns the same score. The
+    /// rewards (history, XP, leaderboard, top user) are applied afterwards by idempotent steps,
+    /// tracked by `rewards_applied` so that a failure part-way is completed by a later call or
+    /// by `apply_pending_rewards`.
+    pub async fn finish_quiz(
+        &self,
+        caller: Caller,
+        user_service: &UserService,
+        quiz_id: ObjectId,
+        expected_version: Option<i64>,
+    ) -> Result<FinishQuizResponse, QuizError> {
+        let now = Utc::now();
+
+        let mut finished = None;
+        for _ in 0..MAX_WRITE_ATTEMPTS {
+            let quiz = self.owned_quiz(caller, quiz_id).await?;
+            // Already finished: nothing to award again, unless an earlier attempt stopped part-way
+            match quiz.status {
+                QuizStatus::Finished => {
+                    finished = Some(quiz);
+                    break;
+                }
+                QuizStatus::Abandoned => return Err(QuizError::QuizAbandoned),
+                QuizStatus::InProgress | QuizStatus::Paused => {}
+            }
+            if expected_version.is_some_and(|v| v != quiz.version) {
+                return Err(QuizError::StaleAttempt);
+            }
+
+            // an exam finished late, e.g. by the expiry task, still ends at its deadline, and a
+            // daily challenge finished the next day at the end of its own
+            let end_time = quiz.exam.as_ref().map_or(now, |exam| exam.deadline.min(now));
+            let end_time = quiz.challenge_ends_at.map_or(end_time, |ends_at| ends_at.min(end_time));
+            let time_bson = bson::to_bson(&end_time).map_err(|_| "Failed to serialize end_time".to_string())?;
+            let mut set = doc! { "end_time": time_bson, "status": "Finished", "rewards_applied": false };
+            set.extend(close_open_pause(&quiz, end_time));
+            let mut update = doc! { "$set": set };
+            if quiz.exam.is_some() {
+                let unanswered = unanswered_as_wrong(&quiz, end_time);
+                if !unanswered.is_empty() {
+                    let unanswered = bson::to_bson(&unanswered).map_err(|_| "Failed to serialize answers".to_string())?;
+                    update.insert("$push", doc! { "answers": { "$each": unanswered } });
+                }
+            }
+            finished = self.write_versioned(&quiz, update).await?;
+            if finished.is_some() {
+                break;
+            }
+        }
+
+        let quiz = finished.ok_or(QuizError::StaleAttempt)?;
+        if !quiz.rewards_applied {
+            self.apply_rewards(&quiz, user_service).await?;
+        }
+
+        let ability = quiz.adaptive.as_ref().map(|a| a.ability);
+        Ok(FinishQuizResponse {
+            quiz_id: quiz_id.to_hex(),
+            score: quiz.score,
+            correct_answers: quiz.answers.iter().filter(|a| a.correct).count(),
+            total_questions: quiz.total_questions(),
+            ability,
+            ability_level: ability.and_then(|a| adaptive::levels_for(a).first().copied()),
+        })
+    }
+
+    /// Finish every exam whose deadline (plus the answer grace) has passed, through the same
+    /// path as `finish_quiz`. Returns how many were finished, plus why any others could not be;
+    /// those are tried again on the next run.
+    pub async fn finish_expired_exams(&self, user_service: &UserService) -> Result<(usize, Vec<String>), String> {
+        let cutoff = bson::DateTime::from_chrono(Utc::now() - self.answer_grace);
+        // only ids and owners, so that one quiz that fails to load cannot stop the others
+        let mut cursor = self.quiz_collection
+            .clone_with_type::<Document>()
+            .find(doc! { "status": "InProgress", "exam.deadline": { "$lte": cutoff } })
+            .projection(doc! { "_id": 1, "user_id": 1 })
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut finished = 0;
+        let mut failed = vec![];
+        while let Some(quiz) = cursor.try_next().await.map_err(|e| e.to_string())? {
+            let (Ok(quiz_id), Ok(user_id)) = (quiz.get_object_id("_id"), quiz.get_object_id("user_id")) else {
+                failed.push(format!("exam {:?}: missing id or owner", quiz.get("_id")));
+                continue;
+            };
+            let owner = Caller { user_id, is_admin: false };
+            match self.finish_quiz(owner, user_service, quiz_id, None).await {
+                Ok(_) => finished += 1,
+                Err(e) => failed.push(format!("exam {}: {}", quiz_id.to_hex(), e)),
+            }
+        }
+        Ok((finished, failed))
+    }
+
+    /// Give quizzes stored before statuses existed the status their `end_time` and `paused`
+    /// fields implied, dropping the latter; returns how many were updated.
+    pub async fn backfill_statuses(&self) -> Result<u64, String> {
+        let quizzes = self.quiz_collection.clone_with_type::<Document>();
+        let mut updated = 0;
+        for (filter, status) in [
+            (doc! { "end_time": { "$ne": null } }, "Finished"),
+            (doc! { "paused": true }, "Paused"),
+            (doc! {}, "InProgress"),
+        ] {
+            let mut filter = filter;
+            filter.insert("status", doc! { "$exists": false });
+            updated += quizzes
+                .update_many(filter, doc! { "$set": { "status": status }, "$unset": { "paused": "" } })
+                .await
+                .map_err(|e| e.to_string())?
+                .modified_count;
+        }
+        Ok(updated)
+    }
+
+    /// Give open quizzes stored before `last_activity_at` was tracked the time of their latest
+    /// activity; returns how many were updated.
+    pub async fn backfill_last_activity(&self) -> Result<usize, String> {
+        let mut cursor = self.quiz_collection
+            .find(doc! { "status": { "$in": ["InProgress", "Paused"] }, "last_activity_at": { "$exists": false } })
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut updated = 0;
+        while let Some(quiz) = cursor.try_next().await.map_err(|e| e.to_string())? {
+            self.quiz_collection
+                .update_one(
+                    doc! { "_id": quiz.id, "last_activity_at": { "$exists": false } },
+                    doc! { "$set": { "last_activity_at": bson::DateTime::from_chrono(quiz.last_activity()) } },
+                )
+                .await
+                .map_err(|e| e.to_string())?;
+            updated += 1;
+        }
+        Ok(updated)
+    }
+
+    /// Mark quizzes nobody has touched for `idle` as abandoned. Exams are left to finish at their
+    /// deadline. Returns how many were abandoned.
+    pub async fn abandon_idle_quizzes(&self, idle: Duration) -> Result<usize, String> {
+        let now = Utc::now();
+        let cutoff = bson::DateTime::from_chrono(now - idle);
+        let mut cursor = self.quiz_collection
+            .find(doc! {
+                "status": { "$in": ["InProgress", "Paused"] },
+                "last_activity_at": { "$lte": cutoff },
+                "exam": { "$exists": false },
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut abandoned = 0;
+        while let Some(quiz) = cursor.try_next().await.map_err(|e| e.to_string())? {
+            if now - quiz.last_activity() < idle {
+                continue;
+            }
+            let mut set = doc! { "status": "Abandoned", "abandoned_at": bson::DateTime::from_chrono(now) };
+            set.extend(close_open_pause(&quiz, now));
+            // a quiz the player came back to meanwhile has moved on a version and is skipped
+            if self.write_versioned(&quiz, doc! { "$set": set }).await.map_err(|e| e.to_string())?.is_some() {
+                abandoned += 1;
+            }
+        }
+        Ok(abandoned)
+    }
+
+    /// Delete quizzes abandoned longer than `retention` ago; returns how many were deleted.
+    pub async fn purge_abandoned_quizzes(&self, retention: Duration) -> Result<u64, String> {
+        let cutoff = bson::DateTime::from_chrono(Utc::now() - retention);
+        let deleted = self.quiz_collection
+            .delete_many(doc! { "status": "Abandoned", "abandoned_at": { "$lt": cutoff } })
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(deleted.deleted_count)
+    }
+
+    /// Complete the rewards of finished quizzes whose finish was interrupted.
+    pub async fn apply_pending_rewards(&self, user_service: &UserService) -> Result<usize, String> {
+        let mut cursor = self.quiz_collection
+            .find(doc! { "status": "Finished", "rewards_applied": false })
             .await
-            .map_err(|_| "Failed to update quiz".to_string())?
-            .ok_or("Quiz not found".to_string())
-    }
-
-   pub async fn finish_quiz(    
-    &self, 
-    user_service: &UserService,  
-    quiz_id: ObjectId
-) -> Result<i32, String> {
-
-    let time_bson = bson::to_bson(&Utc::now()).map_err(|_| "Failed to serialize end_time".to_string())?;
-    let update = doc! { "$set": { "end_time": time_bson, "paused": false } };
-
-    self.quiz_collection
-        .update_one(doc! { "_id": quiz_id }, update, )
-        .await
-        .map_err(|_| "Failed to finish quiz".to_string())?;
-
-    let quiz = self.quiz_collection
-        .find_one(doc! { "_id": quiz_id }, )
-        .await
-        .map_err(|_| "Failed to fetch quiz".to_string())?
-        .ok_or("Quiz not found".to_string())?;
-
-    // ✅ Add quiz history
-    user_service
-        .add_quiz_history(quiz.user_id, quiz.uuid)
-        .await?;
-
-    // ✅ Award XP based on score (customize logic as you like)
-    let xp_earned = quiz.score; // simple: 1 XP per point of score
-    if xp_earned > 0 {
+            .map_err(|e| e.to_string())?;
+
+        let mut applied = 0;
+        while let Some(quiz) = cursor.try_next().await.map_err(|e| e.to_string())? {
+            self.apply_rewards(&quiz, user_service).await?;
+            applied += 1;
+        }
+        Ok(applied)
+    }
+
+    /// Every step can safely run more than once for the same quiz.
+    async fn apply_rewards(&self, quiz: &Quiz, user_service: &UserService) -> Result<(), String> {
+        // History and XP in one guarded update, so XP is only ever granted once per quiz
         user_service
-            .add_xp(quiz.user_id, xp_earned)
+            .record_quiz_result(quiz.user_id, quiz.uuid, quiz.score.max(0))
             .await?;
+
+        // Leaderboard and top user are recomputed from finished quizzes rather than incremented
+        if quiz.mode.ranked() {
+            let total = self.category_total(quiz.user_id, quiz.category_id).await?;
+            self.leaderboard_service.set_score(quiz.user_id, quiz.category_id, total).await?;
+            self.update_category_top_user(quiz.category_id).await?;
+        }
+
+        self.record_reviews(quiz).await?;
+
+        // Carry the ability estimate over to the next adaptive quiz in the category
+        if let Some(state) = &quiz.adaptive {
+            self.ability_collection
+                .update_one(
+                    doc! { "user_id": quiz.user_id, "category_id": quiz.category_id },
+                    doc! { "$set": { "rating": state.ability, "updated_at": bson::DateTime::from_chrono(Utc::now()) } },
+                )
+                .upsert(true)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+
+        self.quiz_collection
+            .update_one(doc! { "_id": quiz.id }, doc! { "$set": { "rewards_applied": true } })
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(())
     }
 
-    // Check and update top user for the category
-    self.update_category_top_user(quiz.user_id, quiz.category_id, user_service).await?;
+    /// Move the quiz's questions along the user's review schedule: weak answers put a question
+    /// into it, and any answer to a scheduled question reschedules it. Answers no newer than
+    /// the one a schedule was last updated from are skipped, so a quiz is only counted once.
+    async fn record_reviews(&self, quiz: &Quiz) -> Result<(), String> {
+        let ids: Vec<ObjectId> = quiz.answers.iter().map(|a| a.question_id).collect();
+        let mut cursor = self.question_collection
+            .find(doc! { "_id": { "$in": &ids } })
+            .await
+            .map_err(|e| e.to_string())?;
+        let mut questions = vec![];
+        while let Some(question) = cursor.try_next().await.map_err(|e| e.to_string())? {
+            questions.push(question);
+        }
 
-    // Update leaderboard
-    self.leaderboard_service.update_leaderboard(quiz.user_id, quiz.category_id, quiz.score).await?;
+        for answer in &quiz.answers {
+            let Some(question) = questions.iter().find(|q| q.id == Some(answer.question_id)) else {
+                continue;
+            };
+            let Some(answered_at) = answer.answered_at.or(quiz.end_time) else {
+                continue;
+            };
+            let quality = spaced_repetition::quality(answer.correct, answer.late, answer.time_taken, question.timer);
 
-    Ok(quiz.score)
-}
+            let existing = self.review_collection
+                .find_one(doc! { "user_id": quiz.user_id, "question_id": answer.question_id })
+                .await
+                .map_err(|e| e.to_string())?;
+            let mut filter = doc! { "user_id": quiz.user_id, "question_id": answer.question_id };
+            let schedule = match &existing {
+                Some(state) if state.last_reviewed_at >= answered_at => continue,
+                Some(state) => {
+                    // only apply over the state that was read
+                    filter.insert("last_reviewed_at", bson::DateTime::from_chrono(state.last_reviewed_at));
+                    spaced_repetition::next(state.schedule(), quality)
+                }
+                None if spaced_repetition::needs_review(quality) => spaced_repetition::next(Schedule::default(), quality),
+                None => continue,
+            };
+
+            let due_at = bson::DateTime::from_chrono(answered_at + Duration::days(schedule.interval_days));
+            let reviewed_at = bson::DateTime::from_chrono(answered_at);
+            self.review_collection
+                .update_one(
+                    filter,
+                    doc! { "$set": {
+                        "category_id": question.category_id,
+                        "ease_factor": schedule.ease_factor,
+                        "interval_days": schedule.interval_days,
+                        "repetitions": schedule.repetitions as i64,
+                        "due_at": due_at,
+                        "last_reviewed_at": reviewed_at,
+                    } },
+                )
+                .upsert(true)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Sum of a user's finished, ranked quiz scores in a category (quizzes from before modes
+    /// existed have no `mode`)
+    async fn category_total(&self, user_id: ObjectId, category_id: ObjectId) -> Result<i32, String> {
+        let mut cursor = self.quiz_collection
+            .aggregate(vec![
+                doc! { "$match": { "user_id": user_id, "category_id": category_id, "status": "Finished", "mode": { "$in": [null, "Standard"] } } },
+                doc! { "$group": { "_id": null, "total": { "$sum": "$score" } } },
+            ])
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let total = cursor.try_next().await.map_err(|e| e.to_string())?;
+        Ok(total.and_then(|doc| doc.get_i32("total").ok()).unwrap_or(0))
+    }
 
    async fn update_category_top_user(
     &self,
-    _user_id: ObjectId,
     category_id: ObjectId,
-    _user_service: &UserService,
 ) -> Result<(), String> {
-    // 1. Fetch all finished quizzes for the category
+    // 1. Fetch all finished quizzes for the category (abandoned ones never count)
     let mut cursor = self.quiz_collection.find(
-        doc! { "category_id": category_id, "end_time": { "$exists": true } },
+        doc! { "category_id": category_id, "status": "Finished", "mode": { "$in": [null, "Standard"] } },
     ).await.map_err(|e| e.to_string())?;
 
     // 2. Group scores by user
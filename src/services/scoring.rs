@@ -0,0 +1,47 @@
+use chrono::Duration;
+
+use crate::models::{question::Difficulty, scoring::{ScoringPolicy, TimeBonus}};
+
+/// What an answer is worth under a policy
+pub struct Scored {
+    pub points: i32,
+    pub correct: bool,
+}
+
+/// Points for one answer. `credit` is the graded share of the answer that is right (0.0-1.0)
+/// and `streak` the number of correct answers given right before it in the quiz.
+pub fn score(
+    policy: &ScoringPolicy,
+    difficulty: &Difficulty,
+    credit: f64,
+    time_taken: Duration,
+    timer: Duration,
+    late: bool,
+    streak: usize,
+) -> Scored {
+    let correct = credit >= 1.0;
+    // too late counts for nothing, but is not punished as a wrong answer
+    if late {
+        return Scored { points: 0, correct };
+    }
+    let credit = if correct || policy.partial_credit { credit } else { 0.0 };
+    if credit <= 0.0 {
+        return Scored { points: -policy.wrong_penalty, correct };
+    }
+
+    let timer_secs = timer.num_milliseconds().max(1) as f64 / 1000.0;
+    let taken_secs = time_taken.num_milliseconds().max(0) as f64 / 1000.0;
+    let bonus = match policy.time_bonus {
+        TimeBonus::None => 0.0,
+        TimeBonus::Flat { points, within_fraction } if taken_secs < timer_secs * within_fraction => points as f64,
+        TimeBonus::Flat { .. } => 0.0,
+        TimeBonus::LinearDecay { max_points } => max_points as f64 * (1.0 - taken_secs / timer_secs).clamp(0.0, 1.0),
+    };
+    let multiplier = match &policy.streak {
+        Some(streak_bonus) if correct => (1.0 + streak_bonus.step * streak as f64).min(streak_bonus.max_multiplier),
+        _ => 1.0,
+    };
+
+    let base = policy.base_points.for_difficulty(difficulty) as f64;
+    Scored { points: ((base + bonus) * credit * multiplier).round() as i32, correct }
+}
@@ -0,0 +1,73 @@
+use std::env;
+
+use async_trait::async_trait;
+use reqwest::Client;
+
+/// Delivers a plain text message to a phone number.
+#[async_trait]
+pub trait SmsSender: Send + Sync {
+    async fn send_sms(&self, phone_e164: &str, body: &str) -> Result<(), String>;
+}
+
+/// Sends SMS through the Twilio Messages API (plain messaging, not Twilio Verify).
+pub struct TwilioSmsSender {
+    client: Client,
+    account_sid: String,
+    auth_token: String,
+    from_number: String,
+}
+
+impl TwilioSmsSender {
+    pub fn new_from_env() -> Result<Self, String> {
+        let account_sid = env::var("TWILIO_ACCOUNT_SID")
+            .map_err(|_| "TWILIO_ACCOUNT_SID must be set".to_string())?;
+        let auth_token = env::var("TWILIO_AUTH_TOKEN")
+            .map_err(|_| "TWILIO_AUTH_TOKEN must be set".to_string())?;
+        let from_number = env::var("TWILIO_FROM_NUMBER")
+            .map_err(|_| "TWILIO_FROM_NUMBER must be set".to_string())?;
+
+        Ok(Self {
+            client: Client::new(),
+            account_sid,
+            auth_token,
+            from_number,
+        })
+    }
+}
+
+#[async_trait]
+impl SmsSender for TwilioSmsSender {
+    async fn send_sms(&self, phone_e164: &str, body: &str) -> Result<(), String> {
+        let url = format!(
+            "https://api.twilio.com/2010-04-01/Accounts/{}/Messages.json",
+            self.account_sid
+        );
+
+        let res = self.client
+            .post(&url)
+            .basic_auth(&self.account_sid, Some(&self.auth_token))
+            .form(&[("To", phone_e164), ("From", self.from_number.as_str()), ("Body", body)])
+            .send()
+            .await
+            .map_err(|e| format!("Twilio SMS request failed: {}", e))?;
+
+        let status = res.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            let body = res.text().await.unwrap_or_else(|_| "<no body>".into());
+            Err(format!("Twilio SMS error {}: {}", status, body))
+        }
+    }
+}
+
+/// Prints messages to stdout instead of sending them (local development).
+pub struct LogSmsSender;
+
+#[async_trait]
+impl SmsSender for LogSmsSender {
+    async fn send_sms(&self, phone_e164: &str, body: &str) -> Result<(), String> {
+        println!("[sms] to {}: {}", phone_e164, body);
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+//! SM-2 style scheduling for review mode. Every answer is graded from 0 to 5; a grade below 3
+//! sends the question back to the start of its schedule, anything else pushes the next review
+//! further out by the question's ease factor, which itself drifts with how easy it was recalled.
+
+use chrono::Duration;
+
+/// Ease factor of a question that has not been reviewed yet
+pub const INITIAL_EASE: f64 = 2.5;
+/// Lowest ease factor; below it, intervals would barely grow
+pub const MIN_EASE: f64 = 1.3;
+/// Lowest grade that still counts as recalled
+pub const PASSING_QUALITY: u8 = 3;
+
+/// Where a question stands in a user's review schedule
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Schedule {
+    pub ease_factor: f64,
+    pub interval_days: i64,
+    /// Reviews passed in a row
+    pub repetitions: u32,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self { ease_factor: INITIAL_EASE, interval_days: 0, repetitions: 0 }
+    }
+}
+
+/// Grades an answer: 1 when wrong or late, 3 when right but slow (over three quarters of the
+/// timer), 5 when right within a quarter of the timer, 4 otherwise
+pub fn quality(correct: bool, late: bool, time_taken: Duration, timer: Duration) -> u8 {
+    if !correct || late {
+        1
+    } else if time_taken * 4 > timer * 3 {
+        3
+    } else if time_taken * 4 <= timer {
+        5
+    } else {
+        4
+    }
+}
+
+/// Whether an answer is weak enough to put a question into review
+pub fn needs_review(quality: u8) -> bool {
+    quality <= PASSING_QUALITY
+}
+
+/// The schedule after a review graded `quality`
+pub fn next(schedule: Schedule, quality: u8) -> Schedule {
+    let quality = quality.min(5);
+    let miss = f64::from(5 - quality);
+    let ease_factor = (schedule.ease_factor + 0.1 - miss * (0.08 + miss * 0.02)).max(MIN_EASE);
+
+    if quality < PASSING_QUALITY {
+        return Schedule { ease_factor, interval_days: 1, repetitions: 0 };
+    }
+
+    let repetitions = schedule.repetitions + 1;
+    let interval_days = match repetitions {
+        1 => 1,
+        2 => 6,
+        _ => (schedule.interval_days as f64 * ease_factor).round() as i64,
+    };
+    Schedule { ease_factor, interval_days, repetitions }
+}
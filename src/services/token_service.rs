@@ -0,0 +1,213 @@
+use std::sync::Arc;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use bson::{doc, oid::ObjectId};
+use chrono::{Duration, Utc};
+use futures::TryStreamExt;
+use mongodb::{Collection, Database};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::models::{
+    token::{RefreshToken, RevokedToken},
+    user::{Role, User},
+};
+use crate::services::jwt_keys::JwtKeyStore;
+use crate::utils::{hash_password, verify_password, Claims};
+
+/// Access + refresh token pair returned on login and refresh
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct TokenPair {
+    pub access_token: String,
+    pub refresh_token: String,
+    /// Access token lifetime in seconds
+    pub expires_in: i64,
+}
+
+pub struct TokenService {
+    refresh_collection: Collection<RefreshToken>,
+    revoked_collection: Collection<RevokedToken>,
+    user_collection: Collection<User>,
+    pub jwt_keys: Arc<JwtKeyStore>,
+    access_ttl: Duration,
+    refresh_ttl: Duration,
+}
+
+impl TokenService {
+    pub fn new(db: Arc<Database>, config: Arc<Config>, jwt_keys: Arc<JwtKeyStore>) -> Self {
+        Self {
+            refresh_collection: db.collection("refresh_tokens"),
+            revoked_collection: db.collection("revoked_tokens"),
+            user_collection: db.collection("users"),
+            jwt_keys,
+            access_ttl: Duration::seconds(config.access_token_ttl_secs),
+            refresh_ttl: Duration::days(config.refresh_token_ttl_days),
+        }
+    }
+
+    /// Issue a fresh token pair, starting a new refresh token family (i.e. a new session).
+    pub async fn issue(&self, user_id: ObjectId, role: &Role) -> Result<TokenPair, String> {
+        self.issue_in_family(user_id, role, Uuid::new_v4()).await
+    }
+
+    async fn issue_in_family(&self, user_id: ObjectId, role: &Role, family_id: Uuid) -> Result<TokenPair, String> {
+        let jti = Uuid::new_v4().to_string();
+        let access_token = self.jwt_keys.sign(&user_id.to_hex(), &role.to_string(), &jti, self.access_ttl).await?;
+
+        let mut secret = [0u8; 32];
+        rand::rng().fill_bytes(&mut secret);
+        let secret = URL_SAFE_NO_PAD.encode(secret);
+
+        let now = Utc::now();
+        let record = RefreshToken {
+            id: Some(ObjectId::new()),
+            user_id,
+            family_id,
+            token_hash: hash_password(&secret),
+            access_jti: jti,
+            created_at: now,
+            expires_at: now + self.refresh_ttl,
+            used_at: None,
+            revoked: false,
+        };
+        let record_id = record.id.unwrap();
+
+        self.refresh_collection
+            .insert_one(record)
+            .await
+            .map_err(|_| "Failed to store refresh token".to_string())?;
+
+        Ok(TokenPair {
+            access_token,
+            // `<record id>.<secret>` lets us look the record up without storing the secret
+            refresh_token: format!("{}.{}", record_id.to_hex(), secret),
+            expires_in: self.access_ttl.num_seconds(),
+        })
+    }
+
+    /// Exchange a refresh token for a new pair. The presented token is consumed; presenting
+    /// an already-used token revokes its whole family, since it means the token has leaked.
+    pub async fn refresh(&self, refresh_token: &str) -> Result<TokenPair, String> {
+        let (record_id, secret) = refresh_token
+            .split_once('.')
+            .and_then(|(id, secret)| ObjectId::parse_str(id).ok().map(|oid| (oid, secret)))
+            .ok_or("Invalid refresh token".to_string())?;
+
+        let record = self.refresh_collection
+            .find_one(doc! { "_id": record_id })
+            .await
+            .map_err(|e| format!("DB query error: {}", e))?
+            .ok_or("Invalid refresh token".to_string())?;
+
+        if !verify_password(&record.token_hash, secret) {
+            return Err("Invalid refresh token".to_string());
+        }
+        if record.revoked || record.expires_at <= Utc::now() {
+            return Err("Refresh token expired or revoked".to_string());
+        }
+
+        // Claim the token atomically so two concurrent refreshes cannot both succeed
+        let used_at = bson::to_bson(&Utc::now()).map_err(|_| "Failed to serialize used_at".to_string())?;
+        let claimed = self.refresh_collection
+            .find_one_and_update(
+                doc! { "_id": record_id, "used_at": null, "revoked": false },
+                doc! { "$set": { "used_at": used_at } },
+            )
+            .await
+            .map_err(|e| format!("DB query error: {}", e))?;
+
+        if claimed.is_none() {
+            self.revoke_family(record.family_id).await?;
+            return Err("Refresh token reuse detected; session revoked".to_string());
+        }
+
+        let user = self.user_collection
+            .find_one(doc! { "_id": record.user_id })
+            .await
+            .map_err(|e| format!("DB query error: {}", e))?
+            .ok_or("User not found".to_string())?;
+
+        self.issue_in_family(record.user_id, &user.role, record.family_id).await
+    }
+
+    /// Log out: revoke the presented access token and, if given, the refresh token's family.
+    pub async fn logout(&self, claims: &Claims, refresh_token: Option<&str>) -> Result<(), String> {
+        let expires_at = chrono::DateTime::from_timestamp(claims.exp as i64, 0).unwrap_or_else(Utc::now);
+        self.revoke_jti(&claims.jti, expires_at).await?;
+
+        if let Some(record_id) = refresh_token
+            .and_then(|t| t.split_once('.'))
+            .and_then(|(id, _)| ObjectId::parse_str(id).ok())
+        {
+            let record = self.refresh_collection
+                .find_one(doc! { "_id": record_id })
+                .await
+                .map_err(|e| format!("DB query error: {}", e))?;
+
+            // Only allow revoking your own sessions
+            if let Some(record) = record.filter(|r| r.user_id.to_hex() == claims.sub) {
+                self.revoke_family(record.family_id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Revoke every session of a user (all refresh token families and their access tokens).
+    pub async fn revoke_all_for_user(&self, user_id: ObjectId) -> Result<(), String> {
+        self.revoke_matching(doc! { "user_id": user_id, "revoked": false }).await
+    }
+
+    async fn revoke_family(&self, family_id: Uuid) -> Result<(), String> {
+        let family_bson = bson::to_bson(&family_id).map_err(|_| "Failed to serialize family id".to_string())?;
+        self.revoke_matching(doc! { "family_id": family_bson, "revoked": false }).await
+    }
+
+    async fn revoke_matching(&self, filter: bson::Document) -> Result<(), String> {
+        let mut cursor = self.refresh_collection
+            .find(filter.clone())
+            .await
+            .map_err(|e| format!("DB query error: {}", e))?;
+
+        // Access tokens issued alongside these refresh tokens may still be live
+        while let Some(record) = cursor.try_next().await.map_err(|e| format!("DB query error: {}", e))? {
+            self.revoke_jti(&record.access_jti, record.created_at + self.access_ttl).await?;
+        }
+
+        self.refresh_collection
+            .update_many(filter, doc! { "$set": { "revoked": true } })
+            .await
+            .map_err(|_| "Failed to revoke refresh tokens".to_string())?;
+        Ok(())
+    }
+
+    async fn revoke_jti(&self, jti: &str, expires_at: chrono::DateTime<Utc>) -> Result<(), String> {
+        let expires_at = bson::DateTime::from_chrono(expires_at);
+        self.revoked_collection
+            .update_one(
+                doc! { "jti": jti },
+                doc! { "$set": { "jti": jti, "expires_at": expires_at } },
+            )
+            .upsert(true)
+            .await
+            .map_err(|_| "Failed to revoke token".to_string())?;
+        Ok(())
+    }
+
+    /// Validate an access token's signature, expiry, issuer and audience and make sure it has not been revoked.
+    pub async fn validate_access_token(&self, token: &str) -> Result<Option<Claims>, String> {
+        let Some(claims) = self.jwt_keys.verify(token).await? else {
+            return Ok(None);
+        };
+
+        let revoked = self.revoked_collection
+            .find_one(doc! { "jti": &claims.jti })
+            .await
+            .map_err(|e| format!("DB query error: {}", e))?
+            .is_some();
+
+        Ok(if revoked { None } else { Some(claims) })
+    }
+}
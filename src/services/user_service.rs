@@ -6,9 +6,14 @@ use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::models::user::{Profile, Role, User, UserResponse};
-use crate::services::phone_verify::TwilioClient;
-use crate::utils::{hash_password, verify_password, generate_jwt};
-use std::sync::Arc;
+use crate::services::invite_service::InviteService;
+use crate::services::login_throttle::{account_key, ip_key, LoginThrottle};
+use crate::services::phone_verify::OtpProvider;
+use crate::services::token_service::{TokenPair, TokenService};
+use crate::utils::{hash_password, normalize_phone, verify_password};
+use futures::TryStreamExt;
+use phonenumber::country;
+use std::sync::{Arc, LazyLock};
 use std::fmt;
 use serde::{Deserialize, Serialize};
 
@@ -17,27 +22,62 @@ use serde::{Deserialize, Serialize};
 pub struct LoginResponse {
     pub message: String,
     pub token: Option<String>,
+    pub refresh_token: Option<String>,
     pub user: Option<UserResponse>,
 }
 
 
+#[derive(Debug, thiserror::Error)]
+pub enum LoginError {
+    /// Same error whether the phone is unknown or the password is wrong
+    #[error("Invalid phone number or password")]
+    InvalidCredentials,
+    #[error("Too many failed login attempts; try again in {retry_after_secs} seconds")]
+    Locked { retry_after_secs: i64 },
+    /// The detail is for the server log; clients only see a fixed message
+    #[error("Login failed")]
+    Internal(String),
+}
+
+/// Verified against when the phone is unknown, so both failure paths cost one Argon2 check
+static DUMMY_PASSWORD_HASH: LazyLock<String> = LazyLock::new(|| hash_password("dummy-password"));
 
 pub struct UserService{
     collection: Collection<User>,
-    twilio: Arc<TwilioClient>
+    otp: Arc<dyn OtpProvider>,
+    pub token_service: Arc<TokenService>,
+    pub invite_service: Arc<InviteService>,
+    login_throttle: Arc<LoginThrottle>,
+    default_region: country::Id,
 }
 
 
 
 
 impl UserService {
-    pub fn new(db: Arc<Database>, tilio: Arc<TwilioClient>) -> Self{
+    pub fn new(
+        db: Arc<Database>,
+        otp: Arc<dyn OtpProvider>,
+        token_service: Arc<TokenService>,
+        invite_service: Arc<InviteService>,
+        login_throttle: Arc<LoginThrottle>,
+        default_region: country::Id,
+    ) -> Self{
         Self{
             collection: db.collection("users"),
-            twilio: tilio
+            otp,
+            token_service,
+            invite_service,
+            login_throttle,
+            default_region,
         }
     }
 
+    /// Every entry point stores and looks up phone numbers in E.164 form
+    fn normalize_phone(&self, phone_number: &str) -> Result<String, String> {
+        normalize_phone(phone_number, self.default_region)
+    }
+
 
         /// Verify OTP and register user
     pub async fn verify_and_register(
@@ -46,10 +86,17 @@ impl UserService {
         code: String,
         username: String,
         password: String,
-        role: Role,
+        invite_code: Option<String>,
     ) -> Result<String, String> {
-        // check OTP with Twilio
-        let ok = self.twilio.check_verification(&phone_number, &code).await?;
+        let phone_number = self.normalize_phone(&phone_number)?;
+
+        // a bad invite must not use up the code; it is only redeemed once the OTP checks out
+        if let Some(invite_code) = &invite_code {
+            self.invite_service.check(invite_code).await?;
+        }
+
+        // check OTP with the configured provider
+        let ok = self.otp.check_verification(&phone_number, &code).await?;
         if !ok {
             return Err("Invalid OTP".into());
         }
@@ -63,9 +110,16 @@ impl UserService {
             return Err("Username already in use".into());
         }
 
+        // roles other than User are only ever granted by redeeming an admin's invite
+        let user_id = ObjectId::new();
+        let role = match &invite_code {
+            Some(invite_code) => self.invite_service.redeem(invite_code, user_id).await?,
+            None => Role::User,
+        };
+
         let password_hash = hash_password(&password);
         let new_user = User {
-            id: None,
+            id: Some(user_id),
             phone_number,
             username,
             password_hash,
@@ -80,14 +134,62 @@ impl UserService {
             quiz_history: vec![],
         };
 
-        let insert_result = self.collection.insert_one(new_user, ).await.map_err(|_| "Failed to register user".to_string())?;
-        Ok(insert_result.inserted_id.as_object_id().unwrap().to_hex())
+        if self.collection.insert_one(new_user, ).await.is_err() {
+            if let Some(invite_code) = &invite_code {
+                self.invite_service.release(invite_code, user_id).await?;
+            }
+            return Err("Failed to register user".to_string());
+        }
+        Ok(user_id.to_hex())
     }
 
         /// Start verification: send OTP
     pub async fn start_phone_verification(&self, phone_number: String) -> Result<(), String> {
-        // phone_number must be E.164 (validate/normalize before calling)
-        self.twilio.send_verification(&phone_number).await
+        let phone_number = self.normalize_phone(&phone_number)?;
+        self.otp.send_verification(&phone_number).await
+    }
+
+    /// Start password reset: send an OTP if the phone belongs to a user.
+    /// Unknown phones succeed silently so the endpoint cannot be used to probe for accounts.
+    pub async fn start_password_reset(&self, phone_number: String) -> Result<(), String> {
+        let phone_number = self.normalize_phone(&phone_number)?;
+        let exists = self.collection
+            .find_one(doc! { "phone_number": &phone_number })
+            .await
+            .map_err(|e| format!("DB error: {}", e))?
+            .is_some();
+
+        // a failed send must look the same as an unknown phone
+        if exists && let Err(e) = self.otp.send_verification(&phone_number).await {
+            eprintln!("Password reset code not sent: {}", e);
+        }
+        Ok(())
+    }
+
+    /// Verify the reset OTP, store the new password and log the user out everywhere.
+    pub async fn reset_password(
+        &self,
+        phone_number: String,
+        code: String,
+        new_password: String,
+    ) -> Result<(), String> {
+        let phone_number = self.normalize_phone(&phone_number)?;
+        let ok = self.otp.check_verification(&phone_number, &code).await?;
+        if !ok {
+            return Err("Invalid OTP".into());
+        }
+
+        let user = self.collection
+            .find_one_and_update(
+                doc! { "phone_number": &phone_number },
+                doc! { "$set": { "password_hash": hash_password(&new_password) } },
+            )
+            .await
+            .map_err(|e| format!("DB error: {}", e))?
+            .ok_or("Invalid OTP".to_string())?;
+
+        let user_id = user.id.ok_or("User record missing id".to_string())?;
+        self.token_service.revoke_all_for_user(user_id).await
     }
 
 
@@ -122,39 +224,107 @@ pub async fn login(
     &self,
     phone_number: String,
     password: String,
-    jwt_secret: &str,
-) -> Result<(UserResponse, String), String> {
-    // return DB-level errors as Err(String) instead of panicking
-    let find_result = self
+    client_ip: Option<String>,
+) -> Result<(UserResponse, TokenPair), LoginError> {
+    let Ok(phone_number) = self.normalize_phone(&phone_number) else {
+        return Err(LoginError::InvalidCredentials);
+    };
+    let account = account_key(&phone_number);
+    let mut keys = vec![account.clone()];
+    if let Some(ip) = client_ip.as_deref() {
+        keys.push(ip_key(ip));
+    }
+
+    if let Some(until) = self.login_throttle.locked_until(&keys).await.map_err(LoginError::Internal)? {
+        let retry_after_secs = (until - chrono::Utc::now()).num_seconds().max(1);
+        return Err(LoginError::Locked { retry_after_secs });
+    }
+
+    // return DB-level errors as Err instead of panicking
+    let user = self
         .collection
         .find_one(doc! { "phone_number": &phone_number }, )
         .await
-        .map_err(|e| format!("DB query error: {}", e))?;
+        .map_err(|e| LoginError::Internal(format!("DB query error: {}", e)))?;
 
+    let password_ok = match &user {
+        Some(user) => verify_password(&user.password_hash, &password),
+        None => {
+            verify_password(&DUMMY_PASSWORD_HASH, &password);
+            false
+        }
+    };
+
+    let user = match user {
+        Some(user) if password_ok => user,
+        _ => {
+            for key in &keys {
+                self.login_throttle.record_failure(key).await.map_err(LoginError::Internal)?;
+            }
+            return Err(LoginError::InvalidCredentials);
+        }
+    };
 
+    self.login_throttle.reset(&account).await.map_err(LoginError::Internal)?;
 
-let user = find_result.ok_or_else(|| "User not found".to_string())?;
+    // ensure id exists before issuing tokens
+    let user_id = user.id.ok_or_else(|| LoginError::Internal("User record missing id".to_string()))?;
 
-if !verify_password(&user.password_hash, &password) {
-    return Err("Invalid password".to_string());
-}
+    // every login starts a new session (refresh token family)
+    let tokens = self.token_service.issue(user_id, &user.role).await.map_err(LoginError::Internal)?;
 
-// ensure id exists and capture role as owned Strings before consuming or cloning user
-let user_id = match user.id.as_ref() {
-    Some(oid) => oid.to_hex(),
-    None => return Err("User record missing id".to_string()),
-};
-let role = user.role.to_string();
+    let user_response: UserResponse = user.into();
 
-// clone user for conversion (avoids moving the original `user`)
-let user_response: UserResponse = user.clone().into();
+    Ok((user_response, tokens))
+}
 
-let token = generate_jwt(&user_id, &role, jwt_secret);
+    /// Admin override: clear the failed-login counter and lockout of a user's account.
+    pub async fn unlock_account(&self, user_id: ObjectId) -> Result<(), String> {
+        let user = self.get_user(user_id).await?;
+        self.login_throttle.reset(&account_key(&user.phone_number)).await
+    }
 
-Ok((user_response, token))
 
-}
+    /// One-off migration: rewrite stored phone numbers to E.164.
+    /// Numbers that cannot be parsed, or that would collide with another user, are left alone
+    /// and returned so they can be fixed by hand.
+    pub async fn normalize_stored_phone_numbers(&self) -> Result<(u64, Vec<String>), String> {
+        let mut cursor = self.collection
+            .find(doc! {})
+            .await
+            .map_err(|e| format!("DB query error: {}", e))?;
+
+        let mut updated = 0;
+        let mut skipped = Vec::new();
+        while let Some(user) = cursor.try_next().await.map_err(|e| format!("DB query error: {}", e))? {
+            let normalized = match self.normalize_phone(&user.phone_number) {
+                Ok(normalized) if normalized == user.phone_number => continue,
+                Ok(normalized) => normalized,
+                Err(e) => {
+                    skipped.push(format!("{}: {}", user.username, e));
+                    continue;
+                }
+            };
+
+            let taken = self.collection
+                .find_one(doc! { "phone_number": &normalized })
+                .await
+                .map_err(|e| format!("DB query error: {}", e))?
+                .is_some();
+            if taken {
+                skipped.push(format!("{}: {} already belongs to another user", user.username, normalized));
+                continue;
+            }
+
+            self.collection
+                .update_one(doc! { "_id": user.id }, doc! { "$set": { "phone_number": normalized } })
+                .await
+                .map_err(|e| format!("DB query error: {}", e))?;
+            updated += 1;
+        }
 
+        Ok((updated, skipped))
+    }
 
     pub async fn update_profile(&self, user_id: ObjectId, profile: Profile) -> Result<(), String> {
         let update_result = self.collection.update_one(
@@ -177,29 +347,18 @@ Ok((user_response, token))
             .ok_or_else(|| format!("User with ID {} not found", user_id))
     }
 
-    pub async fn add_xp(&self, user_id: ObjectId, xp: i32) -> Result<(), String> {
-        let update_result = self.collection.update_one(
-            doc! { "_id": user_id },
-            doc! { "$inc": { "xp": xp } }
-        ).await.map_err(|_| "Failed to add XP".to_string())?;
-
-        if update_result.matched_count == 0 {
-            return Err("User not found".to_string());
-        }
-
-        Ok(())
-    }
-
-    pub async fn add_quiz_history(&self, user_id: ObjectId, quiz_uuid: Uuid) -> Result<(), String> {
+    /// Add a finished quiz to the user's history and award its XP, at most once per quiz.
+    pub async fn record_quiz_result(&self, user_id: ObjectId, quiz_uuid: Uuid, xp: i32) -> Result<(), String> {
         // Serialize the Uuid to its binary representation for MongoDB
         let quiz_uuid_bson = bson::to_bson(&quiz_uuid).map_err(|_| "Failed to serialize quiz Uuid".to_string())?;
 
         let update_result = self.collection.update_one(
-            doc! { "_id": user_id },
-            doc! { "$push": { "quiz_history": quiz_uuid_bson } }
-        ).await.map_err(|_| "Failed to add quiz history".to_string())?;
+            doc! { "_id": user_id, "quiz_history": { "$ne": &quiz_uuid_bson } },
+            doc! { "$push": { "quiz_history": &quiz_uuid_bson }, "$inc": { "xp": xp } }
+        ).await.map_err(|_| "Failed to record quiz result".to_string())?;
 
-        if update_result.matched_count == 0 {
+        // No match either means the quiz was already recorded or the user is gone
+        if update_result.matched_count == 0 && self.get_user(user_id).await.is_err() {
             return Err("User not found".to_string());
         }
 
@@ -0,0 +1,31 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::question::Difficulty;
+    use crate::services::adaptive::{expected_score, levels_for, rating_of, update};
+
+    #[test]
+    fn test_levels_are_ordered_by_distance_from_ability() {
+        assert_eq!(levels_for(rating_of(&Difficulty::Beginner))[0], Difficulty::Beginner);
+        assert_eq!(levels_for(1290.0), vec![Difficulty::Intermediate, Difficulty::Advanced, Difficulty::Beginner, Difficulty::Expert]);
+        assert_eq!(levels_for(5000.0)[0], Difficulty::Expert);
+    }
+
+    #[test]
+    fn test_ability_follows_answers() {
+        let start = rating_of(&Difficulty::Beginner);
+        assert!((expected_score(start, &Difficulty::Beginner) - 0.5).abs() < 1e-9);
+
+        // a strong player climbs a level within two answers
+        let ability = (0..2).fold(start, |ability, answered| update(ability, &Difficulty::Beginner, 1.0, answered));
+        assert_eq!(levels_for(ability)[0], Difficulty::Intermediate);
+
+        // a wrong answer at a level the player was expected to handle drops them back
+        let dropped = update(ability, &Difficulty::Beginner, 0.0, 2);
+        assert_eq!(levels_for(dropped)[0], Difficulty::Beginner);
+
+        // later answers move the estimate less
+        let early = update(start, &Difficulty::Beginner, 1.0, 0) - start;
+        let late = update(start, &Difficulty::Beginner, 1.0, 20) - start;
+        assert!(late < early);
+    }
+}
@@ -11,9 +11,10 @@ mod tests {
     use crate::{
         models::{
             category::{Category, CreateCategoryResponse, CategoryWithTopUserResponse},
-            question::{Difficulty, Question, QuestionType},
+            question::{Difficulty, Question},
             user::{Role, UserResponse},
         },
+        services::quiz_service::Caller,
         tests::common::{create_test_user, setup},
     };
     
@@ -23,9 +24,9 @@ mod tests {
     async fn test_get_categories_with_top_users() {
         // Arrange
         let ctx = setup().await;
-        let admin_user = create_test_user(&ctx.db, Role::Admin, "admin", "+15559998888").await;
-        let top_user = create_test_user(&ctx.db, Role::User, "topgun", "+15551112222").await;
-        let other_user = create_test_user(&ctx.db, Role::User, "secondbest", "+15553334444").await;
+        let admin_user = create_test_user(&ctx.db, Role::Admin, "admin", "+12015550233").await;
+        let top_user = create_test_user(&ctx.db, Role::User, "topgun", "+12015550122").await;
+        let other_user = create_test_user(&ctx.db, Role::User, "secondbest", "+12015550200").await;
 
         // Create a category and questions
         let category_id = ObjectId::new();
@@ -34,6 +35,7 @@ mod tests {
             name: "Test Category".to_string(),
             image_url: Some("test.jpg".to_string()),
             top_user_id: None,
+            scoring_policy: None,
             tags: vec![],
             parent_id: None,
         };
@@ -46,6 +48,8 @@ mod tests {
                 question: format!("Question {}?", i + 1),
                 options: vec!["A".to_string(), "B".to_string()],
                 correct_answer: "A".to_string(),
+                answer_key: None,
+                match_rules: None,
                 difficulty: Difficulty::Beginner,
                 timer: chrono::Duration::seconds(30),
                 tags: vec![],
@@ -56,21 +60,25 @@ mod tests {
         }
 
         // Simulate quizzes being played and finished
-        let top_user_quiz = ctx.services.quiz_service.start_quiz(top_user.id, category_id, Difficulty::Beginner, 5).await.unwrap();
+        let top_caller = Caller { user_id: top_user.id, is_admin: false };
+        let other_caller = Caller { user_id: other_user.id, is_admin: false };
+        let top_user_quiz = ctx.services.quiz_service.start_quiz(top_user.id, category_id, Difficulty::Beginner, 5, true, false).await.unwrap();
         let top_quiz_id = top_user_quiz.id.unwrap();
         // Submit correct answers for top_user (all "A", time_taken=10s for bonus)
         for q_id in top_user_quiz.questions.iter() {
-            ctx.services.quiz_service.submit_answer(top_quiz_id, *q_id, "A".to_string(), 10).await.unwrap();
+            ctx.services.quiz_service.next_question(top_caller, top_quiz_id).await.unwrap();
+            ctx.services.quiz_service.submit_answer(top_caller, top_quiz_id, *q_id, "A".into(), Some(10), None).await.unwrap();
         }
-        ctx.services.quiz_service.finish_quiz(&ctx.services.user_service, top_quiz_id).await.unwrap();
+        ctx.services.quiz_service.finish_quiz(top_caller, &ctx.services.user_service, top_quiz_id, None).await.unwrap();
 
-        let other_user_quiz = ctx.services.quiz_service.start_quiz(other_user.id, category_id, Difficulty::Beginner, 5).await.unwrap();
+        let other_user_quiz = ctx.services.quiz_service.start_quiz(other_user.id, category_id, Difficulty::Beginner, 5, true, false).await.unwrap();
         let other_quiz_id = other_user_quiz.id.unwrap();
         // Submit incorrect answers for other_user (all "B")
         for q_id in other_user_quiz.questions.iter() {
-            ctx.services.quiz_service.submit_answer(other_quiz_id, *q_id, "B".to_string(), 20).await.unwrap();
+            ctx.services.quiz_service.next_question(other_caller, other_quiz_id).await.unwrap();
+            ctx.services.quiz_service.submit_answer(other_caller, other_quiz_id, *q_id, "B".into(), Some(20), None).await.unwrap();
         }
-        ctx.services.quiz_service.finish_quiz(&ctx.services.user_service, other_quiz_id).await.unwrap();
+        ctx.services.quiz_service.finish_quiz(other_caller, &ctx.services.user_service, other_quiz_id, None).await.unwrap();
 
         // Act: Test /admin/categories_with_top_users
         let request = Request::builder()
@@ -112,7 +120,7 @@ mod tests {
     async fn test_create_category() {
         // Arrange
         let ctx = setup().await;
-        let admin_user = create_test_user(&ctx.db, Role::Admin, "admin_cat", "+15559998889").await;
+        let admin_user = create_test_user(&ctx.db, Role::Admin, "admin_cat", "+12015550244").await;
 
         let category_name = "New Test Category".to_string();
         let tags = vec!["tag1".to_string(), "tag2".to_string()];
@@ -170,7 +178,7 @@ mod tests {
     async fn test_create_question() {
         // Arrange
         let ctx = setup().await;
-        let admin_user = create_test_user(&ctx.db, Role::Admin, "admin_q", "+15559998880").await;
+        let admin_user = create_test_user(&ctx.db, Role::Admin, "admin_q", "+12015550211").await;
 
         // Create a category first
         let category_id = ObjectId::new();
@@ -179,44 +187,79 @@ mod tests {
             name: "Question Category".to_string(),
             image_url: None,
             top_user_id: None,
+            scoring_policy: None,
             tags: vec![],
             parent_id: None,
         };
         ctx.db.collection("categories").insert_one(category, ).await.unwrap();
 
-        let question_data = crate::models::question::Question {
-            id: None,
-            category_id,
-            question: "What is the capital of France?".to_string(),
-            options: vec!["Paris".to_string(), "London".to_string(), "Berlin".to_string()],
-            correct_answer: "Paris".to_string(),
-            difficulty: Difficulty::Beginner,
-            timer: chrono::Duration::seconds(30),
-            tags: vec!["geography".to_string()],
-            explanation: "Paris is the capital and most populous city of France.".to_string(),
-            question_type: QuestionType::MultipleChoice,
+        let question_data = serde_json::json!({
+            "category_id": category_id.to_hex(),
+            "text": "What is the capital of France?",
+            "question_type": "MultipleChoice",
+            "options": ["Paris", "London", "Berlin"],
+            "answer": { "type": "Exact", "answer": "Paris" },
+            "explanation": "Paris is the capital and most populous city of France.",
+            "difficulty": "Beginner",
+            "timer_secs": 30,
+            "tags": ["geography"],
+        });
+        let mut mismatched = question_data.clone();
+        mismatched["question_type"] = serde_json::json!("Numeric");
+        let post = |body: &serde_json::Value| {
+            Request::builder()
+                .method("POST")
+                .uri("/admin/questions")
+                .header("Authorization", format!("Bearer {}", admin_user.token))
+                .header("Content-Type", "application/json")
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap()
         };
 
-        let request = Request::builder()
-            .method("POST")
-            .uri("/admin/questions")
-            .header("Authorization", format!("Bearer {}", admin_user.token))
-            .header("Content-Type", "application/json")
-            .body(axum::body::Body::from(serde_json::to_string(&question_data).unwrap()))
-            .unwrap();
-
         // Act
-        let response = ctx.app.clone().oneshot(request).await.unwrap();
+        let response = ctx.app.clone().oneshot(post(&question_data)).await.unwrap();
+        let rejected = ctx.app.clone().oneshot(post(&mismatched)).await.unwrap();
 
         // Assert
         assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(rejected.status(), StatusCode::BAD_REQUEST);
 
         let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
         let create_question_response: crate::models::question::CreateQuestionResponse = serde_json::from_slice(&body).unwrap();
 
         assert_eq!(create_question_response.message, "Question created successfully");
-        assert_eq!(create_question_response.question.text, question_data.question);
-        assert_eq!(create_question_response.question.category_id, question_data.category_id.to_hex());
-        assert_eq!(create_question_response.question.correct_answer, question_data.correct_answer);
+        assert_eq!(create_question_response.question.text, question_data["text"]);
+        assert_eq!(create_question_response.question.category_id, category_id.to_hex());
+        assert_eq!(create_question_response.question.correct_answer, "Paris");
+        assert!(create_question_response.question.id.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_admin_routes_reject_non_admin() {
+        // Arrange
+        let ctx = setup().await;
+        let user = create_test_user(&ctx.db, Role::User, "not_admin", "+12015550222").await;
+
+        // Act: regular user token
+        let request = Request::builder()
+            .uri("/admin/categories")
+            .header("Authorization", format!("Bearer {}", user.token))
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = ctx.app.clone().oneshot(request).await.unwrap();
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // Act: no token at all
+        let request = Request::builder()
+            .method("DELETE")
+            .uri(format!("/admin/questions/{}", ObjectId::new().to_hex()))
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = ctx.app.oneshot(request).await.unwrap();
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
     }
-}
\ No newline at end of file
+}
@@ -4,18 +4,28 @@ mod tests {
         body::to_bytes,
         http::{self, Request, StatusCode},
     };
-    use serde_json::{json, Value};
+    use serde_json::json;
     use tower::ServiceExt;
 
-    use crate::{models::user::Role, services::user_service::LoginResponse};
+    use crate::{models::user::Role, services::{token_service::TokenPair, user_service::LoginResponse}};
 
-    use super::super::common::{create_test_user, json_body, setup};
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+
+    use crate::{
+        config::{Config, OtpProviderKind},
+        models::{invite::CreateInviteResponse, jwt_key::Jwks},
+        services::{jwt_keys::JwtKeyStore, mongo_otp::MongoOtpProvider, phone_verify::OtpProvider, sms::SmsSender},
+    };
+
+    use super::super::common::{create_test_user, json_body, setup, test_config, TEST_OTP_CODE};
 
     #[tokio::test]
     async fn test_login_success() {
         // Arrange
         let ctx = setup().await;
-        let phone_number = "+15551112222".to_string();
+        let phone_number = "+12015550122".to_string();
         let password = "password123".to_string();
         create_test_user(&ctx.db, Role::User, "testuser", &phone_number).await;
 
@@ -45,4 +55,497 @@ mod tests {
         assert!(login_response.token.is_some());
         assert_eq!(login_response.user.unwrap().username, "testuser");
     }
-}
\ No newline at end of file
+
+    async fn login(app: &axum::Router, phone_number: &str) -> LoginResponse {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/login")
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(json_body(&json!({ "phone_number": phone_number, "password": "password123" })))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    async fn refresh(app: &axum::Router, refresh_token: &str) -> axum::response::Response {
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/token/refresh")
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(json_body(&json!({ "refresh_token": refresh_token })))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    async fn get_user(app: &axum::Router, user_id: &str, token: &str) -> StatusCode {
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/user/{}", user_id))
+                    .header(http::header::AUTHORIZATION, format!("Bearer {}", token))
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_refresh_rotation_and_reuse_detection() {
+        // Arrange
+        let ctx = setup().await;
+        let phone_number = "+12015550133";
+        let user = create_test_user(&ctx.db, Role::User, "refresher", phone_number).await;
+        let first = login(&ctx.app, phone_number).await;
+        let first_refresh = first.refresh_token.unwrap();
+
+        // Act: rotate once
+        let response = refresh(&ctx.app, &first_refresh).await;
+
+        // Assert: a new pair is issued and the new access token works
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let rotated: TokenPair = serde_json::from_slice(&body).unwrap();
+        assert_ne!(rotated.refresh_token, first_refresh);
+        assert_eq!(get_user(&ctx.app, &user.id.to_hex(), &rotated.access_token).await, StatusCode::OK);
+
+        // Act: replay the already-used refresh token
+        let response = refresh(&ctx.app, &first_refresh).await;
+
+        // Assert: reuse is rejected and the whole family is revoked
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(refresh(&ctx.app, &rotated.refresh_token).await.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(get_user(&ctx.app, &user.id.to_hex(), &rotated.access_token).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_logout_revokes_tokens() {
+        // Arrange
+        let ctx = setup().await;
+        let phone_number = "+12015550144";
+        let user = create_test_user(&ctx.db, Role::User, "leaver", phone_number).await;
+        let session = login(&ctx.app, phone_number).await;
+        let access_token = session.token.unwrap();
+        let refresh_token = session.refresh_token.unwrap();
+
+        // Act
+        let response = ctx
+            .app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/logout")
+                    .header(http::header::AUTHORIZATION, format!("Bearer {}", access_token))
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(json_body(&json!({ "refresh_token": refresh_token })))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(get_user(&ctx.app, &user.id.to_hex(), &access_token).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(refresh(&ctx.app, &refresh_token).await.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_register_with_local_otp_provider() {
+        // Arrange
+        let ctx = setup().await;
+        let phone_number = "+12015550155";
+
+        // Act: request a code, then confirm with it
+        let response = ctx
+            .app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/start_verification")
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(json_body(&json!({ "phone_number": phone_number })))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let confirm = |code: &str| {
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/confirm_register")
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(json_body(&json!({
+                    "phone_number": phone_number,
+                    "code": code,
+                    "username": "offline_user",
+                    "password": "password123"
+                })))
+                .unwrap()
+        };
+        let wrong = ctx.app.clone().oneshot(confirm("000000")).await.unwrap();
+        let response = ctx.app.clone().oneshot(confirm(TEST_OTP_CODE)).await.unwrap();
+
+        // Assert
+        assert_eq!(wrong.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let session = login(&ctx.app, phone_number).await;
+        assert_eq!(session.user.unwrap().username, "offline_user");
+    }
+
+    /// Captures outgoing messages so the test can read the generated code
+    #[derive(Default)]
+    struct RecordingSmsSender {
+        sent: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl SmsSender for RecordingSmsSender {
+        async fn send_sms(&self, _phone_e164: &str, body: &str) -> Result<(), String> {
+            self.sent.lock().unwrap().push(body.to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mongo_otp_lifecycle() {
+        // Arrange
+        let ctx = setup().await;
+        let sms = Arc::new(RecordingSmsSender::default());
+        let config = Config { otp_provider: OtpProviderKind::Mongo, ..test_config() };
+        let provider = MongoOtpProvider::new(ctx.db.clone(), sms.clone(), &config);
+        let phone_number = "+12015550166";
+
+        // Act: send, then immediately try to resend
+        provider.send_verification(phone_number).await.unwrap();
+        let resend = provider.send_verification(phone_number).await;
+        let message = sms.sent.lock().unwrap()[0].clone();
+        // "Your verification code is 123456. It expires in ..."
+        let code = message.split_whitespace().nth(4).unwrap().trim_end_matches('.').to_string();
+
+        // Assert: the cooldown silently keeps the first code, which is checked and single use
+        assert!(resend.is_ok(), "resend inside the cooldown must look like any other request");
+        assert_eq!(sms.sent.lock().unwrap().len(), 1);
+        assert!(!provider.check_verification(phone_number, "not-it").await.unwrap());
+        assert!(provider.check_verification(phone_number, &code).await.unwrap());
+        assert!(provider.check_verification(phone_number, &code).await.is_err());
+        let stored: bson::Document = ctx.db.collection("otp_codes").find_one(bson::doc! {}).await.unwrap().unwrap();
+        assert_ne!(stored.get_str("code_hash").unwrap(), code);
+    }
+
+    /// Fails its first send, then records messages like `RecordingSmsSender`
+    #[derive(Default)]
+    struct FlakySmsSender {
+        failed_once: Mutex<bool>,
+        sent: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl SmsSender for FlakySmsSender {
+        async fn send_sms(&self, _phone_e164: &str, body: &str) -> Result<(), String> {
+            let mut failed_once = self.failed_once.lock().unwrap();
+            if !*failed_once {
+                *failed_once = true;
+                return Err("SMS gateway unavailable".to_string());
+            }
+            self.sent.lock().unwrap().push(body.to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_otp_send_does_not_start_cooldown() {
+        // Arrange
+        let ctx = setup().await;
+        let sms = Arc::new(FlakySmsSender::default());
+        let config = Config { otp_provider: OtpProviderKind::Mongo, ..test_config() };
+        let provider = MongoOtpProvider::new(ctx.db.clone(), sms.clone(), &config);
+        let phone_number = "+12015550168";
+
+        // Act: the first send fails, the user retries straight away
+        let failed = provider.send_verification(phone_number).await;
+        let retry = provider.send_verification(phone_number).await;
+
+        // Assert: the retry delivered a code that verifies
+        assert!(failed.is_err());
+        assert!(retry.is_ok());
+        let message = sms.sent.lock().unwrap().first().cloned().expect("retry must send a code");
+        let code = message.split_whitespace().nth(4).unwrap().trim_end_matches('.').to_string();
+        assert!(provider.check_verification(phone_number, &code).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_password_reset_revokes_sessions() {
+        // Arrange
+        let ctx = setup().await;
+        let phone_number = "+12015550177";
+        let user = create_test_user(&ctx.db, Role::User, "forgetful", phone_number).await;
+        let session = login(&ctx.app, phone_number).await;
+        let post = |uri: &str, body: serde_json::Value| {
+            Request::builder()
+                .method(http::Method::POST)
+                .uri(uri)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(json_body(&body))
+                .unwrap()
+        };
+
+        // Act
+        let forgot = ctx
+            .app
+            .clone()
+            .oneshot(post("/password/forgot", json!({ "phone_number": phone_number })))
+            .await
+            .unwrap();
+        let reset = ctx
+            .app
+            .clone()
+            .oneshot(post(
+                "/password/reset",
+                json!({ "phone_number": phone_number, "code": TEST_OTP_CODE, "new_password": "new-password456" }),
+            ))
+            .await
+            .unwrap();
+
+        // Assert: old sessions are dead, only the new password works
+        assert_eq!(forgot.status(), StatusCode::ACCEPTED);
+        assert_eq!(reset.status(), StatusCode::NO_CONTENT);
+        assert_eq!(get_user(&ctx.app, &user.id.to_hex(), &session.token.unwrap()).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(refresh(&ctx.app, &session.refresh_token.unwrap()).await.status(), StatusCode::UNAUTHORIZED);
+
+        let old_password = ctx
+            .app
+            .clone()
+            .oneshot(post("/login", json!({ "phone_number": phone_number, "password": "password123" })))
+            .await
+            .unwrap();
+        let new_password = ctx
+            .app
+            .clone()
+            .oneshot(post("/login", json!({ "phone_number": phone_number, "password": "new-password456" })))
+            .await
+            .unwrap();
+        assert_eq!(old_password.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(new_password.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_login_lockout_and_admin_unlock() {
+        // Arrange
+        let ctx = setup().await;
+        let phone_number = "+12015550188";
+        let user = create_test_user(&ctx.db, Role::User, "guessed", phone_number).await;
+        let admin = create_test_user(&ctx.db, Role::Admin, "unlocker", "+12015550199").await;
+        let attempt = |phone: &str, password: &str| {
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/login")
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(json_body(&json!({ "phone_number": phone, "password": password })))
+                .unwrap()
+        };
+
+        // Act: unknown phone vs wrong password
+        let unknown = ctx.app.clone().oneshot(attempt("+12015550100", "password123")).await.unwrap();
+        let unknown_body: LoginResponse = serde_json::from_slice(&to_bytes(unknown.into_body(), usize::MAX).await.unwrap()).unwrap();
+        let mut wrong_body = None;
+        for _ in 0..4 {
+            let response = ctx.app.clone().oneshot(attempt(phone_number, "wrong")).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            wrong_body = Some(to_bytes(response.into_body(), usize::MAX).await.unwrap());
+        }
+        let wrong_body: LoginResponse = serde_json::from_slice(&wrong_body.unwrap()).unwrap();
+
+        // Assert: uniform error, then locked even with the right password
+        assert_eq!(unknown_body.message, wrong_body.message);
+        let locked = ctx.app.clone().oneshot(attempt(phone_number, "password123")).await.unwrap();
+        assert_eq!(locked.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(locked.headers().contains_key(http::header::RETRY_AFTER));
+
+        // Act: admin unlocks the account
+        let unlock = ctx
+            .app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri(format!("/admin/users/{}/unlock", user.id.to_hex()))
+                    .header(http::header::AUTHORIZATION, format!("Bearer {}", admin.token))
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(unlock.status(), StatusCode::NO_CONTENT);
+        let response = ctx.app.clone().oneshot(attempt(phone_number, "password123")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_phone_numbers_are_normalized() {
+        // Arrange: one legacy user stored before normalization, in national format
+        let ctx = setup().await;
+        create_test_user(&ctx.db, Role::User, "legacy", "(201) 555-0199").await;
+
+        // Act
+        let (updated, skipped) = ctx.services.user_service.normalize_stored_phone_numbers().await.unwrap();
+        let national = login(&ctx.app, "201-555-0199").await;
+        let invalid = ctx
+            .app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/login")
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(json_body(&json!({ "phone_number": "12345", "password": "password123" })))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(updated, 1);
+        assert!(skipped.is_empty());
+        assert!(national.token.is_some());
+        assert_eq!(national.user.unwrap().phone_number, "+12015550199");
+        assert_eq!(invalid.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_jwks_and_signing_key_rotation() {
+        // Arrange
+        let ctx = setup().await;
+        let phone_number = "+12015550211";
+        let user = create_test_user(&ctx.db, Role::User, "verifier", phone_number).await;
+        let admin = create_test_user(&ctx.db, Role::Admin, "keymaster", "+12015550212").await;
+        let before = login(&ctx.app, phone_number).await.token.unwrap();
+        let jwks = |app: axum::Router| async move {
+            let response = app
+                .oneshot(Request::builder().uri("/.well-known/jwks.json").body(axum::body::Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<Jwks>(&body).unwrap()
+        };
+        let kid = |token: &str| jsonwebtoken::decode_header(token).unwrap().kid.unwrap();
+
+        // Act
+        let published = jwks(ctx.app.clone()).await;
+        let rotate = ctx
+            .app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/admin/jwt/rotate")
+                    .header(http::header::AUTHORIZATION, format!("Bearer {}", admin.token))
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let after = login(&ctx.app, phone_number).await.token.unwrap();
+        let rotated = jwks(ctx.app.clone()).await;
+
+        // Assert: the old key keeps verifying its tokens while the new one signs
+        assert_eq!(published.keys.len(), 1);
+        assert_eq!(published.keys[0].kid, kid(&before));
+        assert_eq!(published.keys[0].alg, "EdDSA");
+        assert_eq!(rotate.status(), StatusCode::NO_CONTENT);
+        assert_ne!(kid(&after), kid(&before));
+        assert_eq!(rotated.keys.len(), 2);
+        assert_eq!(get_user(&ctx.app, &user.id.to_hex(), &before).await, StatusCode::OK);
+        assert_eq!(get_user(&ctx.app, &user.id.to_hex(), &after).await, StatusCode::OK);
+
+        // Tokens meant for another audience are rejected
+        let foreign = JwtKeyStore::new(ctx.db.clone(), &Config { jwt_audience: "other-service".to_string(), ..test_config() })
+            .sign(&user.id.to_hex(), &Role::User.to_string(), "foreign-jti", chrono::Duration::minutes(5))
+            .await
+            .unwrap();
+        assert_eq!(get_user(&ctx.app, &user.id.to_hex(), &foreign).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_admin_role_requires_invite() {
+        // Arrange
+        let ctx = setup().await;
+        let admin = create_test_user(&ctx.db, Role::Admin, "inviter", "+12015550221").await;
+        let post = |uri: &str, token: Option<&str>, body: serde_json::Value| {
+            let mut request = Request::builder()
+                .method(http::Method::POST)
+                .uri(uri)
+                .header(http::header::CONTENT_TYPE, "application/json");
+            if let Some(token) = token {
+                request = request.header(http::header::AUTHORIZATION, format!("Bearer {}", token));
+            }
+            request.body(json_body(&body)).unwrap()
+        };
+        let register = |phone_number: &'static str, username: &'static str, extra: serde_json::Value| {
+            let app = ctx.app.clone();
+            async move {
+                let sent = app
+                    .clone()
+                    .oneshot(post("/start_verification", None, json!({ "phone_number": phone_number })))
+                    .await
+                    .unwrap();
+                assert_eq!(sent.status(), StatusCode::OK);
+                let mut body = json!({
+                    "phone_number": phone_number,
+                    "code": TEST_OTP_CODE,
+                    "username": username,
+                    "password": "password123"
+                });
+                body.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+                app.oneshot(post("/confirm_register", None, body)).await.unwrap().status()
+            }
+        };
+
+        // Act: asking for a role is ignored, an invite grants it exactly once
+        let self_promoted = register("+12015550222", "wannabe", json!({ "role": "admin" })).await;
+        let created = ctx
+            .app
+            .clone()
+            .oneshot(post("/admin/invites", Some(&admin.token), json!({ "role": "Admin" })))
+            .await
+            .unwrap();
+        assert_eq!(created.status(), StatusCode::CREATED);
+        let body = to_bytes(created.into_body(), usize::MAX).await.unwrap();
+        let invite: CreateInviteResponse = serde_json::from_slice(&body).unwrap();
+        let invited = register("+12015550223", "invited", json!({ "invite_code": invite.code })).await;
+        let reused = register("+12015550224", "reuser", json!({ "invite_code": invite.code })).await;
+        // the rejected invite leaves the code usable for registering without it
+        let confirm_again = post(
+            "/confirm_register",
+            None,
+            json!({ "phone_number": "+12015550224", "code": TEST_OTP_CODE, "username": "reuser", "password": "password123" }),
+        );
+        let without_invite = ctx.app.clone().oneshot(confirm_again).await.unwrap().status();
+
+        // Assert
+        assert_eq!(self_promoted, StatusCode::CREATED);
+        assert!(matches!(login(&ctx.app, "+12015550222").await.user.unwrap().role, Role::User));
+        assert_eq!(invited, StatusCode::CREATED);
+        assert!(matches!(login(&ctx.app, "+12015550223").await.user.unwrap().role, Role::Admin));
+        assert_eq!(reused, StatusCode::BAD_REQUEST);
+        assert_eq!(without_invite, StatusCode::CREATED);
+        assert!(matches!(login(&ctx.app, "+12015550224").await.user.unwrap().role, Role::User));
+    }
+}
@@ -10,17 +10,21 @@ use std::sync::Arc;
 use tower::ServiceExt;
 
 use crate::{
-    config::Config,
-    db::init_db,
+    config::{Config, OtpProviderKind, SmsSenderKind},
+    db::{ensure_indexes, init_db},
     models::user::{Profile, Role, User},
     routes,
     services::{
-        phone_verify::TwilioClient, question_service::QuestionService, quiz_service::QuizService,
-        user_service::UserService,
+        phone_verify::LocalOtpProvider, question_service::QuestionService, quiz_service::QuizService,
+        login_throttle::LoginThrottle, token_service::TokenService, user_service::UserService,
+        jwt_keys::JwtKeyStore, invite_service::InviteService,
     },
-    utils::{generate_jwt, hash_password},
+    utils::hash_password,
 };
 
+/// Code accepted by the local OTP provider used in tests
+pub const TEST_OTP_CODE: &str = "123456";
+
 pub struct TestServices {
     pub user_service: UserService,
     pub quiz_service: QuizService,
@@ -37,35 +41,76 @@ pub struct TestUser {
     pub token: String,
     pub role: Role,
 }
-pub async fn setup() -> TestContext {
-    dotenv::dotenv().ok();
-    unsafe {
-        std::env::set_var("JWT_SECRET", std::env::var("JWT_SECRET").unwrap_or("test_secret".to_string())); // Ensure JWT_SECRET is set
-    }
 
-    let config = Config {
+/// Configuration used by the test app: local OTP provider, short-lived tokens.
+pub fn test_config() -> Config {
+    Config {
         mongodb_uri: std::env::var("MONGODB_TEST_URI")
             .expect("MONGODB_TEST_URI must be set for tests"),
-        jwt_secret: std::env::var("JWT_SECRET").expect("JWT_SECRET must be set"),
+        jwt_issuer: "coding_quix_app".to_string(),
+        jwt_audience: "coding_quix_app".to_string(),
+        jwt_key_rotation_days: 30,
+        answer_grace_secs: 2,
+        invite_ttl_hours: 72,
         port: 8001,
-    };
+        access_token_ttl_secs: 900,
+        refresh_token_ttl_days: 30,
+        // registration runs offline against the in-process provider
+        otp_provider: OtpProviderKind::Local,
+        local_otp_code: Some(TEST_OTP_CODE.to_string()),
+        sms_sender: SmsSenderKind::Log,
+        otp_ttl_secs: 300,
+        otp_max_attempts: 3,
+        otp_resend_cooldown_secs: 60,
+        login_max_failures: 5,
+        login_ip_max_failures: 50,
+        login_backoff_base_secs: 60,
+        login_lockout_secs: 900,
+        trust_forwarded_for: false,
+        default_phone_region: phonenumber::country::Id::US,
+        daily_challenge_timezone: chrono_tz::Tz::UTC,
+        daily_challenge_questions: 3,
+        quiz_idle_timeout_hours: 24,
+        abandoned_quiz_retention_days: Some(30),
+    }
+}
+
+pub async fn setup() -> TestContext {
+    dotenv::dotenv().ok();
+
+    let config = test_config();
 
     let db = Arc::new(init_db(&config.mongodb_uri).await);
 
     // Clean up database and verify
-    for collection in ["users", "quizzes", "categories", "questions"] {
+    for collection in ["users", "quizzes", "categories", "questions", "refresh_tokens", "revoked_tokens", "otp_codes", "login_attempts", "jwt_keys", "invites", "abilities", "review_states", "daily_challenges"] {
         let result = db.collection::<bson::Document>(collection).drop().await;
         println!("Drop {} result: {:?}", collection, result);
         if let Err(e) = result {
             panic!("Failed to drop collection {}: {}", collection, e);
         }
     }
+    ensure_indexes(&db).await.expect("Failed to create indexes");
 
-    let twilio_client = Arc::new(TwilioClient::new_from_env());
+    let otp_provider = Arc::new(LocalOtpProvider::new(Some(TEST_OTP_CODE.to_string())));
     let config_arc = Arc::new(config);
 
-    let user_service = UserService::new(db.clone(), twilio_client.clone());
-    let quiz_service = QuizService::new(db.clone(), Arc::new(crate::services::leaderboard_service::LeaderboardService::new(db.clone())));
+    let jwt_keys = Arc::new(JwtKeyStore::new(db.clone(), &config_arc));
+    let token_service = Arc::new(TokenService::new(db.clone(), config_arc.clone(), jwt_keys));
+    let login_throttle = Arc::new(LoginThrottle::new(db.clone(), &config_arc));
+    let user_service = UserService::new(
+        db.clone(),
+        otp_provider,
+        token_service,
+        Arc::new(InviteService::new(db.clone(), &config_arc)),
+        login_throttle,
+        config_arc.default_phone_region,
+    );
+    let quiz_service = QuizService::new(
+        db.clone(),
+        Arc::new(crate::services::leaderboard_service::LeaderboardService::new(db.clone())),
+        &config_arc,
+    );
     let question_service = QuestionService::new(db.clone());
 
     let app = routes::init_routes(db.clone(), config_arc);
@@ -106,11 +151,16 @@ pub async fn create_test_user(
 
     let user_id = user.id.expect("Test user should have an ID after insertion");
 
-    // Accessing environment variable is safe here as it's for test setup and not in a multi-threaded context where it could cause issues.
-    let jwt_secret = unsafe {
-        std::env::var("JWT_SECRET").expect("JWT_SECRET must be set")
-    };
-    let token = generate_jwt(&user_id.to_hex(), &role.to_string(), &jwt_secret);
+    // Signs with the same keys as the app, picking up the current key from the database
+    let token = JwtKeyStore::new(db.clone(), &test_config())
+        .sign(
+            &user_id.to_hex(),
+            &role.to_string(),
+            &uuid::Uuid::new_v4().to_string(),
+            chrono::Duration::minutes(15),
+        )
+        .await
+        .expect("Failed to sign test token");
 
     TestUser { id: user_id, token, role }
 }
@@ -0,0 +1,33 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{question::Difficulty, quiz::DifficultyShare};
+    use crate::services::composition::allocate;
+
+    fn share(difficulty: Difficulty, percent: u32) -> DifficultyShare {
+        DifficultyShare { difficulty, percent }
+    }
+
+    #[test]
+    fn test_allocation_follows_the_mix_and_adds_up() {
+        let mix = [share(Difficulty::Beginner, 50), share(Difficulty::Intermediate, 30), share(Difficulty::Advanced, 20)];
+
+        assert_eq!(
+            allocate(&mix, 10),
+            Ok(vec![(Difficulty::Beginner, 5), (Difficulty::Intermediate, 3), (Difficulty::Advanced, 2)])
+        );
+        // 3.5 / 2.1 / 1.4: the leftover question goes to the largest remainder
+        assert_eq!(
+            allocate(&mix, 7),
+            Ok(vec![(Difficulty::Beginner, 4), (Difficulty::Intermediate, 2), (Difficulty::Advanced, 1)])
+        );
+        // levels rounded down to nothing are left out
+        assert_eq!(allocate(&mix, 1), Ok(vec![(Difficulty::Beginner, 1)]));
+    }
+
+    #[test]
+    fn test_invalid_mixes_are_rejected() {
+        assert!(allocate(&[], 5).is_err());
+        assert!(allocate(&[share(Difficulty::Beginner, 60), share(Difficulty::Expert, 30)], 5).is_err());
+        assert!(allocate(&[share(Difficulty::Beginner, 50), share(Difficulty::Beginner, 50)], 5).is_err());
+    }
+}
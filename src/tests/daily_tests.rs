@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+    use bson::oid::ObjectId;
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use chrono_tz::Tz;
+
+    use crate::services::daily::{challenge_day, day_bounds, pick, seed};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_day_boundary_follows_the_time_zone() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 17, 20, 0, 0).unwrap();
+        assert_eq!(challenge_day(now, Tz::UTC), date(2026, 10, 17));
+        assert_eq!(challenge_day(now, Tz::Asia__Tokyo), date(2026, 10, 18));
+        assert_eq!(challenge_day(now, Tz::America__Los_Angeles), date(2026, 10, 17));
+
+        let (start, end) = day_bounds(date(2026, 10, 18), Tz::Asia__Tokyo);
+        assert_eq!(start, Utc.with_ymd_and_hms(2026, 10, 17, 15, 0, 0).unwrap());
+        assert_eq!(end - start, chrono::Duration::hours(24));
+    }
+
+    #[test]
+    fn test_days_around_clock_changes_are_not_24_hours() {
+        let (start, end) = day_bounds(date(2026, 11, 1), Tz::America__New_York);
+        assert_eq!(start, Utc.with_ymd_and_hms(2026, 11, 1, 4, 0, 0).unwrap());
+        assert_eq!(end - start, chrono::Duration::hours(25));
+
+        let (start, end) = day_bounds(date(2026, 3, 8), Tz::America__New_York);
+        assert_eq!(end - start, chrono::Duration::hours(23));
+        assert_eq!(start, Utc.with_ymd_and_hms(2026, 3, 8, 5, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_picks_are_shared_per_category_and_day() {
+        let category = ObjectId::new();
+        let pool: Vec<ObjectId> = (0..20).map(|_| ObjectId::new()).collect();
+        let mut reversed = pool.clone();
+        reversed.reverse();
+
+        let today = pick(pool.clone(), 5, seed(category, date(2026, 10, 17)));
+        let again = pick(reversed, 5, seed(category, date(2026, 10, 17)));
+        let tomorrow = pick(pool.clone(), 5, seed(category, date(2026, 10, 18)));
+
+        assert_eq!(today.len(), 5);
+        assert_eq!(today, again);
+        assert_ne!(today, tomorrow);
+        assert_ne!(seed(category, date(2026, 10, 17)), seed(ObjectId::new(), date(2026, 10, 17)));
+        assert_eq!(pick(pool[..3].to_vec(), 5, 1).len(), 3);
+    }
+
+    #[test]
+    fn test_picks_do_not_change_between_releases() {
+        // a published day's questions must come out the same after any upgrade
+        let pool: Vec<ObjectId> = (0..10u8).map(|i| ObjectId::from_bytes([i; 12])).collect();
+        let picked: Vec<u8> = pick(pool, 4, 42).iter().map(|id| id.bytes()[0]).collect();
+        assert_eq!(picked, vec![0, 9, 5, 8]);
+    }
+}
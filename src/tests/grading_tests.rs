@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{
+        question::{AnswerKey, MatchRules, QuestionType},
+        quiz::SubmittedAnswer,
+    };
+    use crate::services::grading::grade;
+
+    fn list(items: &[&str]) -> SubmittedAnswer {
+        SubmittedAnswer::List(items.iter().map(|s| s.to_string()).collect())
+    }
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_multi_select_credits_each_correct_pick() {
+        let key = AnswerKey::MultiSelect { answers: strings(&["Vec", "String"]) };
+
+        assert_eq!(grade(&key, &MatchRules::default(), &list(&["String", "Vec"])), Ok(1.0));
+        assert_eq!(grade(&key, &MatchRules::default(), &list(&["Vec"])), Ok(0.5));
+        assert_eq!(grade(&key, &MatchRules::default(), &list(&["Vec", "String", "i32"])), Ok(0.5));
+        assert_eq!(grade(&key, &MatchRules::default(), &list(&["i32", "u8"])), Ok(0.0));
+        assert!(grade(&key, &MatchRules::default(), &"Vec".into()).is_err());
+    }
+
+    #[test]
+    fn test_ordering_and_blanks() {
+        let order = AnswerKey::Ordering { order: strings(&["let x = 1;", "x + 1"]) };
+        let blanks = AnswerKey::FillInTheBlank { blanks: vec![strings(&["mut"]), strings(&["Vec", "vec!"])] };
+
+        assert_eq!(grade(&order, &MatchRules::default(), &list(&["let x = 1;", "x + 1"])), Ok(1.0));
+        assert_eq!(grade(&order, &MatchRules::default(), &list(&["x + 1", "let x = 1;"])), Ok(0.0));
+        assert_eq!(grade(&blanks, &MatchRules::default_for(QuestionType::FillInTheBlank), &list(&[" MUT ", "vec!"])), Ok(1.0));
+        assert_eq!(grade(&blanks, &MatchRules::default_for(QuestionType::FillInTheBlank), &list(&["mut", "HashMap"])), Ok(0.5));
+        assert!(grade(&blanks, &MatchRules::default_for(QuestionType::FillInTheBlank), &list(&["mut"])).is_err());
+    }
+
+    #[test]
+    fn test_numeric_answers_within_tolerance() {
+        let key = AnswerKey::Numeric { value: 2.5, tolerance: 0.01 };
+
+        assert_eq!(grade(&key, &MatchRules::default(), &SubmittedAnswer::Number(2.505)), Ok(1.0));
+        assert_eq!(grade(&key, &MatchRules::default(), &"2.51".into()), Ok(1.0));
+        assert_eq!(grade(&key, &MatchRules::default(), &SubmittedAnswer::Number(2.6)), Ok(0.0));
+        assert!(grade(&key, &MatchRules::default(), &"pi".into()).is_err());
+    }
+
+    #[test]
+    fn test_answer_keys_are_validated_against_the_question() {
+        let options = strings(&["b", "a", "c"]);
+
+        assert!(AnswerKey::Ordering { order: strings(&["a", "b", "c"]) }.validate(QuestionType::Ordering, &options).is_ok());
+        // shown as stored, so the options must not already be the answer
+        assert!(AnswerKey::Ordering { order: options.clone() }.validate(QuestionType::Ordering, &options).is_err());
+        assert!(AnswerKey::MultiSelect { answers: strings(&["a", "d"]) }.validate(QuestionType::MultiSelect, &options).is_err());
+        assert!(AnswerKey::Numeric { value: 1.0, tolerance: -1.0 }.validate(QuestionType::Numeric, &[]).is_err());
+        assert!(AnswerKey::Exact { answer: "a".to_string() }.validate(QuestionType::Numeric, &options).is_err());
+    }
+
+    #[test]
+    fn test_free_text_match_rules() {
+        let key = AnswerKey::Exact { answer: "Hello World".to_string() };
+        let lenient = MatchRules { collapse_whitespace: true, case_insensitive: true, ..Default::default() };
+        let aliased = MatchRules { trim: true, aliases: strings(&["Hello, World"]), pattern: Some(r"Hello,? +World!?".to_string()), ..Default::default() };
+
+        assert_eq!(grade(&key, &MatchRules::default(), &"Hello World\n".into()), Ok(0.0));
+        assert_eq!(grade(&key, &lenient, &"  hello   WORLD\n".into()), Ok(1.0));
+        assert_eq!(grade(&key, &aliased, &"Hello, World\n".into()), Ok(1.0));
+        assert_eq!(grade(&key, &aliased, &"Hello   World!".into()), Ok(1.0));
+        assert_eq!(grade(&key, &aliased, &"Goodbye World".into()), Ok(0.0));
+        // the pattern has to match the whole answer
+        assert_eq!(grade(&key, &aliased, &"Hello World! Bye".into()), Ok(0.0));
+    }
+
+    #[test]
+    fn test_match_rules_are_validated() {
+        let exact = AnswerKey::Exact { answer: "42".to_string() };
+        let numeric = AnswerKey::Numeric { value: 42.0, tolerance: 0.0 };
+        let bad_pattern = MatchRules { pattern: Some("(unclosed".to_string()), ..Default::default() };
+        let aliases = MatchRules { aliases: strings(&["forty-two"]), ..Default::default() };
+
+        assert!(bad_pattern.validate(&exact).is_err());
+        assert!(aliases.validate(&exact).is_ok());
+        assert!(aliases.validate(&numeric).is_err());
+    }
+}
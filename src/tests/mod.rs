@@ -1,4 +1,13 @@
+mod adaptive_tests;
 mod admin_tests;
 mod auth_tests;
 mod common;
-mod quiz_tests;
\ No newline at end of file
+mod composition_tests;
+mod daily_tests;
+mod grading_tests;
+mod pause_tests;
+mod phone_tests;
+mod quiz_tests;
+mod scoring_tests;
+mod spaced_repetition_tests;
+
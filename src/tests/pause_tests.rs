@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    use bson::oid::ObjectId;
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use uuid::Uuid;
+
+    use crate::models::{
+        question::Difficulty,
+        quiz::{PauseInterval, Quiz, QuizMode, QuizStatus},
+        scoring::ScoringPolicy,
+    };
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 17, 12, 0, 0).unwrap() + Duration::seconds(secs)
+    }
+
+    fn pause(from: i64, to: Option<i64>, allowance_ends: i64) -> PauseInterval {
+        PauseInterval {
+            question_id: None,
+            paused_at: at(from),
+            resumed_at: to.map(at),
+            allowance_ends_at: at(allowance_ends),
+        }
+    }
+
+    fn quiz_with(pauses: Vec<PauseInterval>) -> Quiz {
+        Quiz {
+            id: None,
+            uuid: Uuid::new_v4(),
+            user_id: ObjectId::new(),
+            category_id: ObjectId::new(),
+            difficulty: Difficulty::Beginner,
+            questions: vec![],
+            answers: vec![],
+            served: vec![],
+            sequential: true,
+            start_time: at(0),
+            end_time: None,
+            score: 0,
+            status: if pauses.iter().any(|p| p.resumed_at.is_none()) { QuizStatus::Paused } else { QuizStatus::InProgress },
+            abandoned_at: None,
+            pauses,
+            rewards_applied: false,
+            version: 0,
+            scoring: ScoringPolicy::default(),
+            adaptive: None,
+            composition: None,
+            mode: QuizMode::Standard,
+            daily_challenge_id: None,
+            last_activity_at: None,
+            challenge_ends_at: None,
+            exam: None,
+        }
+    }
+
+    #[test]
+    fn test_only_the_overlap_with_a_pause_is_taken_off() {
+        let quiz = quiz_with(vec![pause(10, Some(40), 600), pause(100, Some(110), 600)]);
+
+        assert_eq!(quiz.paused_between(at(0), at(200)), Duration::seconds(40));
+        // a question served during the first pause and answered during the second
+        assert_eq!(quiz.paused_between(at(30), at(105)), Duration::seconds(15));
+        assert_eq!(quiz.paused_between(at(50), at(90)), Duration::zero());
+    }
+
+    #[test]
+    fn test_pauses_beyond_the_allowance_keep_the_clock_running() {
+        // paused for five minutes with only one minute of allowance left
+        let quiz = quiz_with(vec![pause(0, Some(300), 60)]);
+        assert_eq!(quiz.paused_between(at(0), at(300)), Duration::seconds(60));
+
+        // an open pause counts up to the end of the window, within its allowance
+        let open = quiz_with(vec![pause(100, None, 400)]);
+        assert_eq!(open.paused_between(at(0), at(250)), Duration::seconds(150));
+        assert_eq!(open.paused_between(at(0), at(1000)), Duration::seconds(300));
+    }
+
+    #[test]
+    fn test_daily_challenges_allow_the_fewest_pauses() {
+        let daily = QuizMode::Daily.pause_limits();
+        let standard = QuizMode::Standard.pause_limits();
+        assert!(daily.max_pauses < standard.max_pauses);
+        assert!(daily.max_total < standard.max_total);
+        assert!(QuizMode::Review.pause_limits().max_pauses >= standard.max_pauses);
+    }
+}
@@ -0,0 +1,23 @@
+#[cfg(test)]
+mod tests {
+    use phonenumber::country;
+
+    use crate::utils::normalize_phone;
+
+    #[test]
+    fn test_normalize_phone_formats_to_e164() {
+        let expected = Ok("+2348031234567".to_string());
+
+        assert_eq!(normalize_phone("+234 803 123 4567", country::Id::NG), expected);
+        assert_eq!(normalize_phone("08031234567", country::Id::NG), expected);
+        assert_eq!(normalize_phone("+234-803-123-4567", country::Id::US), expected);
+    }
+
+    #[test]
+    fn test_normalize_phone_rejects_invalid_and_non_mobile() {
+        assert!(normalize_phone("not a number", country::Id::NG).is_err());
+        assert!(normalize_phone("+234 803", country::Id::NG).is_err());
+        // US toll-free numbers cannot receive SMS
+        assert!(normalize_phone("+1 800 234 5678", country::Id::NG).is_err());
+    }
+}
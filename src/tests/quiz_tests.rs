@@ -8,19 +8,22 @@ mod tests {
     use serde_json::json;
     use tower::ServiceExt;
 
+    use crate::services::quiz_service::{Caller, QuizError};
     use crate::models::{
         category::Category,
+        quiz::{DifficultyShare, QuizComposition, QuizMode, QuizStatus},
         question::{Difficulty, Question},
+        scoring::{ScoringPolicy, TimeBonus},
         user::Role,
     };
 
-    use super::super::common::{create_test_user, json_body, setup};
+    use super::super::common::{create_test_user, json_body, setup, TestContext};
 
     #[tokio::test]
     async fn test_finish_quiz_endpoint() {
         // Arrange
         let ctx = setup().await;
-        let user = create_test_user(&ctx.db, Role::User, "quizuser", "+15550001111").await;
+        let user = create_test_user(&ctx.db, Role::User, "quizuser", "+12015550111").await;
 
         // 1. Create a category and some questions for the quiz
         let category_id = ObjectId::new();
@@ -31,6 +34,7 @@ mod tests {
             name: "Test Category".to_string(),
             image_url: Some("test.jpg".to_string()),
             top_user_id: None,
+            scoring_policy: None,
         };
         ctx.db
             .collection("categories")
@@ -45,6 +49,8 @@ mod tests {
                 question: format!("Question {}?", i + 1),
                 options: vec!["A".to_string(), "B".to_string()],
                 correct_answer: "A".to_string(),
+                answer_key: None,
+                match_rules: None,
                 difficulty: Difficulty::Beginner,
                 timer: chrono::Duration::seconds(30),
                 explanation: "".to_string(),
@@ -58,7 +64,7 @@ mod tests {
         let start_quiz_result = ctx
             .services
             .quiz_service
-            .start_quiz(user.id, category_id, Difficulty::Beginner, 5)
+            .start_quiz(user.id, category_id, Difficulty::Beginner, 5, true, false)
             .await;
 
         assert!(
@@ -83,4 +89,916 @@ mod tests {
         // Assert
         assert_eq!(response.status(), StatusCode::OK, "Finish quiz endpoint failed");
     }
-}
\ No newline at end of file
+
+    /// Inserts a category with `count` Beginner questions whose correct answer is "A".
+    async fn seed_category(ctx: &TestContext, count: usize) -> ObjectId {
+        let category_id = ObjectId::new();
+        let category = Category {
+            id: Some(category_id),
+            tags: vec![],
+            parent_id: None,
+            name: "Seeded Category".to_string(),
+            image_url: None,
+            top_user_id: None,
+            scoring_policy: None,
+        };
+        ctx.db.collection("categories").insert_one(category).await.unwrap();
+
+        for i in 0..count {
+            let question = Question {
+                id: Some(ObjectId::new()),
+                category_id,
+                question: format!("Question {}?", i + 1),
+                options: vec!["A".to_string(), "B".to_string()],
+                correct_answer: "A".to_string(),
+                answer_key: None,
+                match_rules: None,
+                difficulty: Difficulty::Beginner,
+                timer: chrono::Duration::seconds(30),
+                explanation: "".to_string(),
+                tags: vec![],
+                question_type: crate::models::question::QuestionType::MultipleChoice,
+            };
+            ctx.db.collection("questions").insert_one(question).await.unwrap();
+        }
+        category_id
+    }
+
+    #[tokio::test]
+    async fn test_quiz_ownership_is_enforced() {
+        // Arrange
+        let ctx = setup().await;
+        let owner = create_test_user(&ctx.db, Role::User, "owner", "+12015550231").await;
+        let intruder = create_test_user(&ctx.db, Role::User, "intruder", "+12015550232").await;
+        let admin = create_test_user(&ctx.db, Role::Admin, "auditor", "+12015550233").await;
+        let category_id = seed_category(&ctx, 3).await;
+        let quiz = ctx
+            .services
+            .quiz_service
+            .start_quiz(owner.id, category_id, Difficulty::Beginner, 3, true, false)
+            .await
+            .unwrap();
+        let quiz_id = quiz.id.unwrap().to_hex();
+        let request = |method: &str, uri: String, token: &str, body: serde_json::Value| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(http::header::AUTHORIZATION, format!("Bearer {}", token))
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(json_body(&body))
+                .unwrap()
+        };
+        let answer = json!({ "question_id": quiz.questions[0].to_hex(), "answer": "A", "time_taken": 5 });
+
+        // Act
+        let mut statuses = Vec::new();
+        for token in [&intruder.token, &admin.token] {
+            for (uri, body) in [
+                (format!("/quiz/{}/answer", quiz_id), answer.clone()),
+                (format!("/quiz/{}/pause", quiz_id), json!({ "paused": true })),
+                (format!("/quiz/{}/finish", quiz_id), json!({})),
+            ] {
+                statuses.push(ctx.app.clone().oneshot(request("POST", uri, token, body)).await.unwrap().status());
+            }
+        }
+        let intruder_read = ctx.app.clone().oneshot(request("GET", format!("/quiz/{}", quiz_id), &intruder.token, json!({}))).await.unwrap();
+        let admin_read = ctx.app.clone().oneshot(request("GET", format!("/quiz/{}", quiz_id), &admin.token, json!({}))).await.unwrap();
+        let owner_next = ctx.app.clone().oneshot(request("GET", format!("/quiz/{}/next", quiz_id), &owner.token, json!({}))).await.unwrap();
+        let owner_answer = ctx.app.clone().oneshot(request("POST", format!("/quiz/{}/answer", quiz_id), &owner.token, answer)).await.unwrap();
+
+        // Assert: nobody but the owner can change the quiz; admins can read it
+        assert!(statuses.iter().all(|s| *s == StatusCode::FORBIDDEN), "{:?}", statuses);
+        assert_eq!(intruder_read.status(), StatusCode::FORBIDDEN);
+        assert_eq!(admin_read.status(), StatusCode::OK);
+        assert_eq!(owner_next.status(), StatusCode::OK);
+        assert_eq!(owner_answer.status(), StatusCode::OK);
+        let body = body::to_bytes(admin_read.into_body(), usize::MAX).await.unwrap();
+        let read: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(read["answers"].as_array().unwrap().len(), 0);
+        assert_eq!(read["user_id"], owner.id.to_hex());
+    }
+
+    #[tokio::test]
+    async fn test_answer_timing_is_measured_by_server() {
+        // Arrange: the first question was served a minute ago and has a 30s timer
+        let ctx = setup().await;
+        let user = create_test_user(&ctx.db, Role::User, "slowpoke", "+12015550241").await;
+        let category_id = seed_category(&ctx, 3).await;
+        let quiz = ctx
+            .services
+            .quiz_service
+            .start_quiz(user.id, category_id, Difficulty::Beginner, 3, true, false)
+            .await
+            .unwrap();
+        let quiz_id = quiz.id.unwrap();
+        let next = || {
+            Request::builder()
+                .uri(format!("/quiz/{}/next", quiz_id.to_hex()))
+                .header(http::header::AUTHORIZATION, format!("Bearer {}", user.token))
+                .body(body::Body::empty())
+                .unwrap()
+        };
+        assert_eq!(ctx.app.clone().oneshot(next()).await.unwrap().status(), StatusCode::OK);
+        let served_at = bson::DateTime::from_chrono(chrono::Utc::now() - chrono::Duration::seconds(60));
+        ctx.db
+            .collection::<bson::Document>("quizzes")
+            .update_one(bson::doc! { "_id": quiz_id }, bson::doc! { "$set": { "served.0.served_at": served_at } })
+            .await
+            .unwrap();
+        let answer = |question_id: ObjectId| {
+            Request::builder()
+                .method(http::Method::POST)
+                .uri(format!("/quiz/{}/answer", quiz_id.to_hex()))
+                .header(http::header::AUTHORIZATION, format!("Bearer {}", user.token))
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(json_body(&json!({ "question_id": question_id.to_hex(), "answer": "A", "time_taken": 1 })))
+                .unwrap()
+        };
+
+        // Act
+        let skipped = ctx.app.clone().oneshot(answer(quiz.questions[1])).await.unwrap();
+        let late = ctx.app.clone().oneshot(answer(quiz.questions[0])).await.unwrap();
+        let unserved = ctx.app.clone().oneshot(answer(quiz.questions[1])).await.unwrap();
+        assert_eq!(ctx.app.clone().oneshot(next()).await.unwrap().status(), StatusCode::OK);
+        let on_time = ctx.app.clone().oneshot(answer(quiz.questions[1])).await.unwrap();
+
+        // Assert: the client's claimed 1s is stored but ignored
+        assert_eq!(skipped.status(), StatusCode::CONFLICT);
+        assert_eq!(late.status(), StatusCode::OK);
+        assert_eq!(unserved.status(), StatusCode::CONFLICT);
+        assert_eq!(on_time.status(), StatusCode::OK);
+        let body = body::to_bytes(on_time.into_body(), usize::MAX).await.unwrap();
+        let quiz: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let first = &quiz["answers"][0];
+        assert_eq!(first["late"], true);
+        assert_eq!(first["correct"], true);
+        assert!(first["time_taken"].as_i64().unwrap() >= 60);
+        assert_eq!(first["client_time_taken"], 1);
+        assert_eq!(quiz["answers"][1]["late"], false);
+        // only the on-time answer scores: 5 base points + 10 speed bonus
+        assert_eq!(quiz["score"], 15);
+    }
+
+    #[tokio::test]
+    async fn test_question_delivery_hides_answers_until_reviewable() {
+        // Arrange
+        let ctx = setup().await;
+        let user = create_test_user(&ctx.db, Role::User, "player", "+12015550242").await;
+        let category_id = seed_category(&ctx, 3).await;
+        let quiz = ctx
+            .services
+            .quiz_service
+            .start_quiz(user.id, category_id, Difficulty::Beginner, 3, true, false)
+            .await
+            .unwrap();
+        let quiz_id = quiz.id.unwrap().to_hex();
+        let get = |uri: String| {
+            let app = ctx.app.clone();
+            let token = user.token.clone();
+            async move {
+                let response = app
+                    .oneshot(
+                        Request::builder()
+                            .uri(uri)
+                            .header(http::header::AUTHORIZATION, format!("Bearer {}", token))
+                            .body(body::Body::empty())
+                            .unwrap(),
+                    )
+                    .await
+                    .unwrap();
+                let status = response.status();
+                let bytes = body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap_or_default())
+            }
+        };
+        let post = |uri: String, body: serde_json::Value| {
+            Request::builder()
+                .method(http::Method::POST)
+                .uri(uri)
+                .header(http::header::AUTHORIZATION, format!("Bearer {}", user.token))
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(json_body(&body))
+                .unwrap()
+        };
+
+        // Act + Assert: the current question comes without its answer
+        let (status, current) = get(format!("/quiz/{}/next", quiz_id)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(current["index"], 0);
+        assert_eq!(current["question_id"], quiz.questions[0].to_hex());
+        assert!(current["review"].is_null());
+        assert!(!current.to_string().contains("correct_answer"));
+        let (ahead, _) = get(format!("/quiz/{}/questions/1", quiz_id)).await;
+        assert_eq!(ahead, StatusCode::CONFLICT);
+
+        // once answered, the question can be reviewed
+        let answer = json!({ "question_id": quiz.questions[0].to_hex(), "answer": "B" });
+        let answered = ctx.app.clone().oneshot(post(format!("/quiz/{}/answer", quiz_id), answer)).await.unwrap();
+        assert_eq!(answered.status(), StatusCode::OK);
+        let (_, reviewed) = get(format!("/quiz/{}/questions/0", quiz_id)).await;
+        assert_eq!(reviewed["review"]["your_answer"], "B");
+        assert_eq!(reviewed["review"]["correct"], false);
+        assert_eq!(reviewed["review"]["correct_answer"], "A");
+
+        // after finishing, every question is reviewable
+        let finished = ctx.app.clone().oneshot(post(format!("/quiz/{}/finish", quiz_id), json!({}))).await.unwrap();
+        assert_eq!(finished.status(), StatusCode::OK);
+        let (status, last) = get(format!("/quiz/{}/questions/2", quiz_id)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(last["review"]["your_answer"].is_null());
+        assert_eq!(last["review"]["correct_answer"], "A");
+        let (no_more, _) = get(format!("/quiz/{}/next", quiz_id)).await;
+        assert_eq!(no_more, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_and_duplicate_answers_are_rejected() {
+        // Arrange: a free-order quiz with every question served up front
+        let ctx = setup().await;
+        let user = create_test_user(&ctx.db, Role::User, "repeater", "+12015550243").await;
+        let category_id = seed_category(&ctx, 3).await;
+        let quiz = ctx
+            .services
+            .quiz_service
+            .start_quiz(user.id, category_id, Difficulty::Beginner, 3, false, false)
+            .await
+            .unwrap();
+        let quiz_id = quiz.id.unwrap().to_hex();
+        let send = |method: &str, uri: String, body: serde_json::Value| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(http::header::AUTHORIZATION, format!("Bearer {}", user.token))
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(json_body(&body))
+                .unwrap();
+            let app = ctx.app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let bytes = body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                let code = serde_json::from_slice::<serde_json::Value>(&bytes).unwrap_or_default()["code"].clone();
+                (status, code)
+            }
+        };
+        for n in 0..3 {
+            let (status, _) = send("GET", format!("/quiz/{}/questions/{}", quiz_id, n), json!({})).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let answer = |question_id: ObjectId| json!({ "question_id": question_id.to_hex(), "answer": "A" });
+        let uri = format!("/quiz/{}/answer", quiz_id);
+
+        // Act
+        let foreign = send("POST", uri.clone(), answer(ObjectId::new())).await;
+        let last_first = send("POST", uri.clone(), answer(quiz.questions[2])).await;
+        let duplicate = send("POST", uri.clone(), answer(quiz.questions[2])).await;
+        send("POST", format!("/quiz/{}/finish", quiz_id), json!({})).await;
+        let after_finish = send("POST", uri.clone(), answer(quiz.questions[0])).await;
+
+        // Assert
+        assert_eq!(foreign, (StatusCode::BAD_REQUEST, json!("question_not_in_quiz")));
+        assert_eq!(last_first.0, StatusCode::OK);
+        assert_eq!(duplicate, (StatusCode::CONFLICT, json!("already_answered")));
+        assert_eq!(after_finish, (StatusCode::CONFLICT, json!("quiz_finished")));
+        let stored = ctx.services.quiz_service.get_quiz(Caller { user_id: user.id, is_admin: false }, quiz.id.unwrap()).await.unwrap();
+        assert_eq!(stored.answers.len(), 1);
+        assert_eq!(stored.score, 15);
+    }
+
+    #[tokio::test]
+    async fn test_finishing_twice_grants_rewards_once() {
+        // Arrange: a quiz with one correct answer
+        let ctx = setup().await;
+        let user = create_test_user(&ctx.db, Role::User, "finisher", "+12015550244").await;
+        let caller = Caller { user_id: user.id, is_admin: false };
+        let category_id = seed_category(&ctx, 2).await;
+        let quiz_service = &ctx.services.quiz_service;
+        let quiz = quiz_service
+            .start_quiz(user.id, category_id, Difficulty::Beginner, 2, true, false)
+            .await
+            .unwrap();
+        let quiz_id = quiz.id.unwrap();
+        let question = quiz_service.next_question(caller, quiz_id).await.unwrap();
+        let question_id = ObjectId::parse_str(&question.question_id).unwrap();
+        quiz_service.submit_answer(caller, quiz_id, question_id, "A".into(), None, None).await.unwrap();
+
+        // Act: finish twice, then replay the reward step as the sweeper would after a crash
+        let first = quiz_service.finish_quiz(caller, &ctx.services.user_service, quiz_id, None).await.unwrap().score;
+        let second = quiz_service.finish_quiz(caller, &ctx.services.user_service, quiz_id, None).await.unwrap().score;
+        ctx.db
+            .collection::<bson::Document>("quizzes")
+            .update_one(bson::doc! { "_id": quiz_id }, bson::doc! { "$set": { "rewards_applied": false } })
+            .await
+            .unwrap();
+        let swept = quiz_service.apply_pending_rewards(&ctx.services.user_service).await.unwrap();
+
+        // Assert
+        assert_eq!(first, second);
+        assert_eq!(swept, 1);
+        let stored = ctx.services.user_service.get_user(user.id).await.unwrap();
+        assert_eq!(stored.xp, first);
+        assert_eq!(stored.quiz_history.len(), 1);
+        let leaderboard = crate::services::leaderboard_service::LeaderboardService::new(ctx.db.clone())
+            .get_leaderboard(category_id)
+            .await
+            .unwrap();
+        assert_eq!(leaderboard.len(), 1);
+        assert_eq!(leaderboard[0].score, first);
+        let stored_quiz = quiz_service.get_quiz(caller, quiz_id).await.unwrap();
+        assert!(stored_quiz.rewards_applied);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_answers_are_serialized() {
+        // Arrange: a free-order quiz with every question served
+        let ctx = setup().await;
+        let user = create_test_user(&ctx.db, Role::User, "doubletap", "+12015550245").await;
+        let caller = Caller { user_id: user.id, is_admin: false };
+        let category_id = seed_category(&ctx, 3).await;
+        let quiz_service = &ctx.services.quiz_service;
+        let quiz = quiz_service
+            .start_quiz(user.id, category_id, Difficulty::Beginner, 3, false, false)
+            .await
+            .unwrap();
+        let quiz_id = quiz.id.unwrap();
+        for n in 0..3 {
+            quiz_service.question_at(caller, quiz_id, n).await.unwrap();
+        }
+        let submit = |question_id: ObjectId, version: Option<i64>| {
+            quiz_service.submit_answer(caller, quiz_id, question_id, "A".into(), None, version)
+        };
+
+        // Act: answers racing each other, then a client acting on the version it saw at the start
+        let (first, second, repeat) = tokio::join!(
+            submit(quiz.questions[0], None),
+            submit(quiz.questions[1], None),
+            submit(quiz.questions[0], None),
+        );
+        let stale = submit(quiz.questions[2], Some(quiz.version)).await;
+        let stale_pause = quiz_service.pause_quiz(caller, quiz_id, true, Some(quiz.version)).await;
+
+        // Assert: no answer overwrote another, and the duplicate was caught
+        assert!(first.is_ok());
+        assert!(second.is_ok());
+        assert!(matches!(repeat, Err(QuizError::AlreadyAnswered)));
+        assert!(matches!(stale, Err(QuizError::StaleAttempt)));
+        assert!(matches!(stale_pause, Err(QuizError::StaleAttempt)));
+        let stored = quiz_service.get_quiz(caller, quiz_id).await.unwrap();
+        assert_eq!(stored.answers.len(), 2);
+        assert_eq!(stored.score, 30);
+        assert_eq!(stored.version, 2);
+        let current = submit(quiz.questions[2], Some(stored.version)).await.unwrap();
+        assert_eq!(current.answers.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_category_scoring_policy_applies_to_new_quizzes() {
+        // Arrange: a category that takes points away for wrong answers
+        let ctx = setup().await;
+        let admin = create_test_user(&ctx.db, Role::Admin, "scorekeeper", "+12015550246").await;
+        let user = create_test_user(&ctx.db, Role::User, "penalized", "+12015550247").await;
+        let caller = Caller { user_id: user.id, is_admin: false };
+        let category_id = seed_category(&ctx, 2).await;
+        let policy = ScoringPolicy { time_bonus: TimeBonus::None, wrong_penalty: 3, ..Default::default() };
+        let put = |body: serde_json::Value| {
+            Request::builder()
+                .method("PUT")
+                .uri(format!("/admin/categories/{}/scoring", category_id.to_hex()))
+                .header(http::header::AUTHORIZATION, format!("Bearer {}", admin.token))
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(json_body(&body))
+                .unwrap()
+        };
+
+        let mut negative = json!(policy);
+        negative["wrong_penalty"] = json!(-1);
+
+        // Act
+        let invalid = ctx.app.clone().oneshot(put(json!({ "scoring_policy": negative }))).await.unwrap();
+        let updated = ctx.app.clone().oneshot(put(json!({ "scoring_policy": policy }))).await.unwrap();
+        let quiz_service = &ctx.services.quiz_service;
+        let quiz = quiz_service
+            .start_quiz(user.id, category_id, Difficulty::Beginner, 2, true, false)
+            .await
+            .unwrap();
+        let quiz_id = quiz.id.unwrap();
+        for answer in ["A", "B"] {
+            let question = quiz_service.next_question(caller, quiz_id).await.unwrap();
+            let question_id = ObjectId::parse_str(&question.question_id).unwrap();
+            quiz_service.submit_answer(caller, quiz_id, question_id, answer.into(), None, None).await.unwrap();
+        }
+
+        // Assert
+        assert_eq!(invalid.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(updated.status(), StatusCode::OK);
+        assert_eq!(quiz.scoring, policy);
+        let stored = quiz_service.get_quiz(caller, quiz_id).await.unwrap();
+        assert_eq!(stored.answers.iter().map(|a| a.points).collect::<Vec<_>>(), vec![Some(5), Some(-3)]);
+        assert_eq!(stored.score, 2);
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_quiz_raises_difficulty_for_strong_players() {
+        // Arrange: beginner and intermediate questions in one category
+        let ctx = setup().await;
+        let user = create_test_user(&ctx.db, Role::User, "climber", "+12015550248").await;
+        let caller = Caller { user_id: user.id, is_admin: false };
+        let category_id = seed_category(&ctx, 3).await;
+        for i in 0..3 {
+            let question = Question {
+                id: Some(ObjectId::new()),
+                category_id,
+                question: format!("Harder question {}?", i + 1),
+                options: vec!["A".to_string(), "B".to_string()],
+                correct_answer: "A".to_string(),
+                answer_key: None,
+                match_rules: None,
+                difficulty: Difficulty::Intermediate,
+                timer: chrono::Duration::seconds(30),
+                explanation: "".to_string(),
+                tags: vec![],
+                question_type: crate::models::question::QuestionType::MultipleChoice,
+            };
+            ctx.db.collection("questions").insert_one(question).await.unwrap();
+        }
+        let quiz_service = &ctx.services.quiz_service;
+
+        // Act: answer every question correctly
+        let quiz = quiz_service
+            .start_quiz(user.id, category_id, Difficulty::Beginner, 3, false, true)
+            .await
+            .unwrap();
+        let quiz_id = quiz.id.unwrap();
+        let mut difficulties = vec![];
+        for _ in 0..3 {
+            let question = quiz_service.next_question(caller, quiz_id).await.unwrap();
+            difficulties.push(question.difficulty);
+            let question_id = ObjectId::parse_str(&question.question_id).unwrap();
+            quiz_service.submit_answer(caller, quiz_id, question_id, "A".into(), None, None).await.unwrap();
+        }
+        let result = quiz_service.finish_quiz(caller, &ctx.services.user_service, quiz_id, None).await.unwrap();
+        let next_quiz = quiz_service
+            .start_quiz(user.id, category_id, Difficulty::Beginner, 2, true, true)
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(quiz.questions.len(), 1);
+        assert!(quiz.sequential);
+        assert_eq!(difficulties, vec![Difficulty::Beginner, Difficulty::Beginner, Difficulty::Intermediate]);
+        assert_eq!(result.total_questions, 3);
+        assert_eq!(result.correct_answers, 3);
+        assert!(result.ability.unwrap() > crate::services::adaptive::rating_of(&Difficulty::Beginner));
+        assert_eq!(result.ability_level, Some(Difficulty::Intermediate));
+        // the next adaptive quiz picks up where this one left off
+        assert_eq!(next_quiz.adaptive.unwrap().ability, result.ability.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_returning_player_starts_another_adaptive_quiz() {
+        // Arrange: a player who has already finished an adaptive quiz in the category
+        let ctx = setup().await;
+        let user = create_test_user(&ctx.db, Role::User, "regular", "+12015550256").await;
+        let caller = Caller { user_id: user.id, is_admin: false };
+        let category_id = seed_category(&ctx, 3).await;
+        let quiz_service = &ctx.services.quiz_service;
+        let user_service = &ctx.services.user_service;
+        let play = || async {
+            let quiz = quiz_service
+                .start_quiz(user.id, category_id, Difficulty::Beginner, 1, true, true)
+                .await
+                .unwrap();
+            let quiz_id = quiz.id.unwrap();
+            let question = quiz_service.next_question(caller, quiz_id).await.unwrap();
+            let question_id = ObjectId::parse_str(&question.question_id).unwrap();
+            quiz_service.submit_answer(caller, quiz_id, question_id, "A".into(), None, None).await.unwrap();
+            quiz_service.finish_quiz(caller, user_service, quiz_id, None).await.unwrap()
+        };
+        play().await;
+
+        // Act: come back for two more, so the saved ability is read and then overwritten
+        let second = play().await;
+        let third = quiz_service
+            .start_quiz(user.id, category_id, Difficulty::Beginner, 1, true, true)
+            .await;
+
+        // Assert
+        assert_eq!(third.unwrap().adaptive.unwrap().ability, second.ability.unwrap());
+        let saved = ctx.db
+            .collection::<bson::Document>("abilities")
+            .find_one(bson::doc! { "user_id": user.id, "category_id": category_id })
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(saved.get_datetime("updated_at").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_composed_quiz_spans_subcategories_tags_and_difficulties() {
+        // Arrange: a parent category with beginner questions and a subcategory with harder
+        // ones, half of them tagged
+        let ctx = setup().await;
+        let user = create_test_user(&ctx.db, Role::User, "composer", "+12015550249").await;
+        let parent_id = seed_category(&ctx, 4).await;
+        let child_id = ObjectId::new();
+        let child = Category {
+            id: Some(child_id),
+            tags: vec![],
+            parent_id: Some(parent_id),
+            name: "Subcategory".to_string(),
+            image_url: None,
+            top_user_id: None,
+            scoring_policy: None,
+        };
+        ctx.db.collection("categories").insert_one(child).await.unwrap();
+        for i in 0..4 {
+            let question = Question {
+                id: Some(ObjectId::new()),
+                category_id: child_id,
+                question: format!("Advanced question {}?", i + 1),
+                options: vec!["A".to_string(), "B".to_string()],
+                correct_answer: "A".to_string(),
+                answer_key: None,
+                match_rules: None,
+                difficulty: Difficulty::Advanced,
+                timer: chrono::Duration::seconds(30),
+                explanation: "".to_string(),
+                tags: if i % 2 == 0 { vec!["ownership".to_string()] } else { vec![] },
+                question_type: crate::models::question::QuestionType::MultipleChoice,
+            };
+            ctx.db.collection("questions").insert_one(question).await.unwrap();
+        }
+        let half_and_half = vec![
+            DifficultyShare { difficulty: Difficulty::Beginner, percent: 50 },
+            DifficultyShare { difficulty: Difficulty::Advanced, percent: 50 },
+        ];
+        let only_advanced = vec![DifficultyShare { difficulty: Difficulty::Advanced, percent: 100 }];
+        let composition = |include_subcategories: bool, tags: &[&str], difficulty_mix: &Vec<DifficultyShare>| QuizComposition {
+            category_ids: vec![parent_id],
+            include_subcategories,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            difficulty_mix: difficulty_mix.clone(),
+        };
+        let quiz_service = &ctx.services.quiz_service;
+
+        // Act
+        let quiz = quiz_service.compose_quiz(user.id, composition(true, &[], &half_and_half), 6, true).await;
+        let without_subcategories = quiz_service.compose_quiz(user.id, composition(false, &[], &half_and_half), 6, true).await;
+        let tagged = quiz_service.compose_quiz(user.id, composition(true, &["ownership"], &only_advanced), 2, true).await;
+        let too_many_tagged = quiz_service.compose_quiz(user.id, composition(true, &["ownership"], &only_advanced), 3, true).await;
+
+        // Assert
+        let quiz = quiz.unwrap();
+        assert_eq!(quiz.questions.len(), 6);
+        assert_eq!(quiz.category_id, parent_id);
+        let mut from_child = ctx.db
+            .collection::<Question>("questions")
+            .find(bson::doc! { "_id": { "$in": &quiz.questions }, "category_id": child_id })
+            .await
+            .unwrap();
+        let mut advanced = 0;
+        while futures::TryStreamExt::try_next(&mut from_child).await.unwrap().is_some() {
+            advanced += 1;
+        }
+        assert_eq!(advanced, 3);
+        assert!(without_subcategories.is_err());
+        assert_eq!(tagged.unwrap().questions.len(), 2);
+        assert!(too_many_tagged.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_wrong_answers_come_back_for_review() {
+        // Arrange: a quiz with one wrong and one right answer
+        let ctx = setup().await;
+        let user = create_test_user(&ctx.db, Role::User, "reviewer", "+12015550250").await;
+        let caller = Caller { user_id: user.id, is_admin: false };
+        let category_id = seed_category(&ctx, 2).await;
+        let quiz_service = &ctx.services.quiz_service;
+        let user_service = &ctx.services.user_service;
+        let quiz = quiz_service
+            .start_quiz(user.id, category_id, Difficulty::Beginner, 2, true, false)
+            .await
+            .unwrap();
+        let quiz_id = quiz.id.unwrap();
+        for answer in ["B", "A"] {
+            let question = quiz_service.next_question(caller, quiz_id).await.unwrap();
+            let question_id = ObjectId::parse_str(&question.question_id).unwrap();
+            quiz_service.submit_answer(caller, quiz_id, question_id, answer.into(), None, None).await.unwrap();
+        }
+        let standard_score = quiz_service.finish_quiz(caller, user_service, quiz_id, None).await.unwrap().score;
+
+        // Act: nothing is due until the wrong answer's interval has passed
+        let before = quiz_service.due_reviews(user.id).await.unwrap();
+        let too_early = quiz_service.start_review(user.id, None).await;
+        ctx.db
+            .collection::<bson::Document>("review_states")
+            .update_many(
+                bson::doc! { "user_id": user.id },
+                bson::doc! { "$set": { "due_at": bson::DateTime::from_chrono(chrono::Utc::now() - chrono::Duration::hours(1)) } },
+            )
+            .await
+            .unwrap();
+        let after = quiz_service.due_reviews(user.id).await.unwrap();
+        let review = quiz_service.start_review(user.id, None).await.unwrap();
+        let second_review = quiz_service.start_review(user.id, None).await;
+        let review_id = review.id.unwrap();
+        quiz_service.next_question(caller, review_id).await.unwrap();
+        quiz_service.submit_answer(caller, review_id, review.questions[0], "A".into(), None, None).await.unwrap();
+        quiz_service.finish_quiz(caller, user_service, review_id, None).await.unwrap();
+        // replaying the reward step must not count the review twice
+        ctx.db
+            .collection::<bson::Document>("quizzes")
+            .update_one(bson::doc! { "_id": review_id }, bson::doc! { "$set": { "rewards_applied": false } })
+            .await
+            .unwrap();
+        quiz_service.apply_pending_rewards(user_service).await.unwrap();
+
+        // Assert: only the wrong answer was scheduled, and passing it pushed it out
+        assert_eq!((before.due, before.scheduled), (0, 1));
+        assert!(before.next_due_at.is_some());
+        assert!(too_early.is_err());
+        assert_eq!(after.due, 1);
+        assert_eq!(review.mode, QuizMode::Review);
+        assert!(matches!(second_review, Err(QuizError::ReviewInProgress)));
+        assert_eq!(review.questions, vec![quiz.questions[0]]);
+        let state = ctx.db
+            .collection::<bson::Document>("review_states")
+            .find_one(bson::doc! { "user_id": user.id })
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(state.get_i64("repetitions").unwrap(), 1);
+        assert_eq!(state.get_i64("interval_days").unwrap(), 1);
+        let done = quiz_service.due_reviews(user.id).await.unwrap();
+        assert_eq!((done.due, done.scheduled), (0, 1));
+        // the review session earned XP but no leaderboard credit
+        let leaderboard = crate::services::leaderboard_service::LeaderboardService::new(ctx.db.clone())
+            .get_leaderboard(category_id)
+            .await
+            .unwrap();
+        assert_eq!(leaderboard[0].score, standard_score);
+        assert_eq!(user_service.get_user(user.id).await.unwrap().quiz_history.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_daily_challenge_is_shared_and_attempted_once() {
+        // Arrange: two players and a category with more questions than a challenge takes
+        let ctx = setup().await;
+        let first = create_test_user(&ctx.db, Role::User, "early_bird", "+12015550251").await;
+        let second = create_test_user(&ctx.db, Role::User, "night_owl", "+12015550252").await;
+        let category_id = seed_category(&ctx, 5).await;
+        let quiz_service = &ctx.services.quiz_service;
+        let daily = &quiz_service.daily_challenge_service;
+
+        // Act
+        let generated = daily.generate_all(daily.today()).await.unwrap();
+        let first_quiz = quiz_service.start_daily_challenge(first.id, category_id).await.unwrap();
+        let second_quiz = quiz_service.start_daily_challenge(second.id, category_id).await.unwrap();
+        let retry = quiz_service.start_daily_challenge(first.id, category_id).await;
+        let caller = Caller { user_id: first.id, is_admin: false };
+        let quiz_id = first_quiz.id.unwrap();
+        for _ in 0..first_quiz.questions.len() {
+            let question = quiz_service.next_question(caller, quiz_id).await.unwrap();
+            let question_id = ObjectId::parse_str(&question.question_id).unwrap();
+            quiz_service.submit_answer(caller, quiz_id, question_id, "A".into(), None, None).await.unwrap();
+        }
+        quiz_service.finish_quiz(caller, &ctx.services.user_service, quiz_id, None).await.unwrap();
+        let second_caller = Caller { user_id: second.id, is_admin: false };
+        quiz_service.finish_quiz(second_caller, &ctx.services.user_service, second_quiz.id.unwrap(), None).await.unwrap();
+        let regenerated = daily.generate_all(daily.today()).await.unwrap();
+        let challenge = quiz_service.daily_challenge(first.id, category_id).await.unwrap();
+
+        // Assert: same questions for everyone, one attempt each, ranked apart from the category
+        assert_eq!(generated, 1);
+        assert_eq!(regenerated, 0);
+        assert_eq!(first_quiz.questions.len(), 3);
+        assert_eq!(first_quiz.questions, second_quiz.questions);
+        assert_eq!(first_quiz.mode, QuizMode::Daily);
+        assert!(matches!(retry, Err(QuizError::DailyChallengeTaken)));
+        assert_eq!(challenge.your_quiz_id, Some(quiz_id.to_hex()));
+        let leaderboard = daily.leaderboard(category_id, daily.today()).await.unwrap();
+        assert_eq!(leaderboard.len(), 2);
+        assert_eq!((leaderboard[0].user_id, leaderboard[0].rank), (first.id, 1));
+        assert_eq!(leaderboard[1].user_id, second.id);
+        let category_leaderboard = crate::services::leaderboard_service::LeaderboardService::new(ctx.db.clone())
+            .get_leaderboard(category_id)
+            .await
+            .unwrap();
+        assert!(category_leaderboard.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_daily_challenge_closes_at_the_end_of_its_day() {
+        // Arrange: an attempt with one answer in, started two hours ago on a day that ended an
+        // hour ago
+        let ctx = setup().await;
+        let user = create_test_user(&ctx.db, Role::User, "latecomer", "+12015550257").await;
+        let caller = Caller { user_id: user.id, is_admin: false };
+        let category_id = seed_category(&ctx, 3).await;
+        let quiz_service = &ctx.services.quiz_service;
+        let quiz = quiz_service.start_daily_challenge(user.id, category_id).await.unwrap();
+        let quiz_id = quiz.id.unwrap();
+        let question = quiz_service.next_question(caller, quiz_id).await.unwrap();
+        let question_id = ObjectId::parse_str(&question.question_id).unwrap();
+        quiz_service.submit_answer(caller, quiz_id, question_id, "A".into(), None, None).await.unwrap();
+        let ends_at = chrono::Utc::now() - chrono::Duration::hours(1);
+        ctx.db
+            .collection::<bson::Document>("quizzes")
+            .update_one(
+                bson::doc! { "_id": quiz_id },
+                bson::doc! { "$set": {
+                    "start_time": (chrono::Utc::now() - chrono::Duration::hours(2)).to_rfc3339(),
+                    "challenge_ends_at": bson::DateTime::from_chrono(ends_at),
+                } },
+            )
+            .await
+            .unwrap();
+
+        // Act: keep playing the next day
+        let next = quiz_service.next_question(caller, quiz_id).await;
+        let late_answer = quiz_service.submit_answer(caller, quiz_id, quiz.questions[1], "A".into(), None, None).await;
+        let result = quiz_service.finish_quiz(caller, &ctx.services.user_service, quiz_id, None).await.unwrap();
+
+        // Assert: only the answer from the challenge's day counts, and the attempt ended with it
+        assert!(quiz.challenge_ends_at.is_some());
+        assert!(matches!(next, Err(QuizError::DailyChallengeClosed)));
+        assert!(matches!(late_answer, Err(QuizError::DailyChallengeClosed)));
+        assert_eq!(result.correct_answers, 1);
+        let stored = quiz_service.get_quiz(caller, quiz_id).await.unwrap();
+        assert_eq!(stored.end_time.map(|end| end.timestamp_millis()), Some(ends_at.timestamp_millis()));
+    }
+
+    #[tokio::test]
+    async fn test_expired_exams_are_finished_automatically() {
+        // Arrange: an exam with one of its three questions answered, and ahead of it an expired
+        // exam whose stored document no longer loads
+        let ctx = setup().await;
+        let user = create_test_user(&ctx.db, Role::User, "examinee", "+12015550253").await;
+        let caller = Caller { user_id: user.id, is_admin: false };
+        let category_id = seed_category(&ctx, 3).await;
+        let quiz_service = &ctx.services.quiz_service;
+        let user_service = &ctx.services.user_service;
+        let no_time = quiz_service.start_exam(user.id, category_id, Difficulty::Beginner, 3, false, 0).await;
+        let broken = quiz_service
+            .start_exam(user.id, category_id, Difficulty::Beginner, 3, false, 600)
+            .await
+            .unwrap();
+        let exam = quiz_service
+            .start_exam(user.id, category_id, Difficulty::Beginner, 3, false, 600)
+            .await
+            .unwrap();
+        let quiz_id = exam.id.unwrap();
+        quiz_service.question_at(caller, quiz_id, 0).await.unwrap();
+        quiz_service.question_at(caller, quiz_id, 1).await.unwrap();
+        quiz_service.submit_answer(caller, quiz_id, exam.questions[0], "A".into(), None, None).await.unwrap();
+        let pause = quiz_service.pause_quiz(caller, quiz_id, true, None).await;
+        let (not_yet, _) = quiz_service.finish_expired_exams(user_service).await.unwrap();
+
+        // Act: let the deadline pass
+        let deadline = chrono::Utc::now() - chrono::Duration::minutes(1);
+        let quizzes = ctx.db.collection::<bson::Document>("quizzes");
+        quizzes
+            .update_many(
+                bson::doc! { "_id": { "$in": [quiz_id, broken.id.unwrap()] } },
+                bson::doc! { "$set": { "exam.deadline": bson::DateTime::from_chrono(deadline) } },
+            )
+            .await
+            .unwrap();
+        quizzes
+            .update_one(bson::doc! { "_id": broken.id }, bson::doc! { "$set": { "score": "not a number" } })
+            .await
+            .unwrap();
+        let late_answer = quiz_service.submit_answer(caller, quiz_id, exam.questions[1], "A".into(), None, None).await;
+        let late_fetch = quiz_service.question_at(caller, quiz_id, 2).await;
+        let (finished, failed) = quiz_service.finish_expired_exams(user_service).await.unwrap();
+        let (again, _) = quiz_service.finish_expired_exams(user_service).await.unwrap();
+
+        // Assert: closed at the deadline, the unanswered questions counted as wrong
+        assert!(no_time.is_err());
+        assert!(matches!(pause, Err(QuizError::BadRequest(_))));
+        assert_eq!(not_yet, 0);
+        assert!(matches!(late_answer, Err(QuizError::TimeUp)));
+        assert!(matches!(late_fetch, Err(QuizError::TimeUp)));
+        assert_eq!((finished, again), (1, 0));
+        assert_eq!(failed.len(), 1);
+        assert!(failed[0].contains(&broken.id.unwrap().to_hex()));
+        let stored = quiz_service.get_quiz(caller, quiz_id).await.unwrap();
+        assert!(stored.rewards_applied);
+        assert_eq!(stored.end_time.unwrap().timestamp_millis(), deadline.timestamp_millis());
+        assert_eq!(stored.answers.len(), 3);
+        assert_eq!(stored.answers.iter().filter(|a| a.correct).count(), 1);
+        assert!(stored.answers[1..].iter().all(|a| a.answer.is_none() && a.points == Some(0)));
+        assert_eq!(user_service.get_user(user.id).await.unwrap().quiz_history.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pauses_hide_the_question_and_are_limited() {
+        // Arrange: a quiz with its first question on screen
+        let ctx = setup().await;
+        let user = create_test_user(&ctx.db, Role::User, "pauser", "+12015550254").await;
+        let caller = Caller { user_id: user.id, is_admin: false };
+        let category_id = seed_category(&ctx, 2).await;
+        let quiz_service = &ctx.services.quiz_service;
+        let quiz = quiz_service
+            .start_quiz(user.id, category_id, Difficulty::Beginner, 2, true, false)
+            .await
+            .unwrap();
+        let quiz_id = quiz.id.unwrap();
+        quiz_service.next_question(caller, quiz_id).await.unwrap();
+
+        // Act: pause, try to peek, resume; then use up the standard mode's pauses
+        let paused = quiz_service.pause_quiz(caller, quiz_id, true, None).await.unwrap();
+        let peek = quiz_service.next_question(caller, quiz_id).await;
+        let answer_while_paused = quiz_service.submit_answer(caller, quiz_id, quiz.questions[0], "A".into(), None, None).await;
+        let resumed = quiz_service.pause_quiz(caller, quiz_id, false, None).await.unwrap();
+        let reread = quiz_service.get_quiz(caller, quiz_id).await;
+        let shown = quiz_service.next_question(caller, quiz_id).await;
+        for _ in 1..QuizMode::Standard.pause_limits().max_pauses {
+            quiz_service.pause_quiz(caller, quiz_id, true, None).await.unwrap();
+            quiz_service.pause_quiz(caller, quiz_id, false, None).await.unwrap();
+        }
+        let one_too_many = quiz_service.pause_quiz(caller, quiz_id, true, None).await;
+        let finished = quiz_service.finish_quiz(caller, &ctx.services.user_service, quiz_id, None).await;
+        let stored = quiz_service.get_quiz(caller, quiz_id).await.unwrap();
+
+        // Assert
+        assert_eq!(paused.pauses.len(), 1);
+        assert_eq!(paused.pauses[0].question_id, Some(quiz.questions[0]));
+        assert!(paused.pauses[0].resumed_at.is_none());
+        assert!(matches!(peek, Err(QuizError::QuizPaused)));
+        assert!(matches!(answer_while_paused, Err(QuizError::QuizPaused)));
+        assert_eq!(resumed.status, QuizStatus::InProgress);
+        assert!(resumed.pauses[0].resumed_at.is_some());
+        assert_eq!(reread.unwrap().pauses[0].resumed_at, resumed.pauses[0].resumed_at);
+        assert!(shown.is_ok());
+        assert!(matches!(one_too_many, Err(QuizError::PauseLimitReached)));
+        assert!(finished.is_ok());
+        assert_eq!(stored.status, QuizStatus::Finished);
+        assert_eq!(stored.pauses.len(), QuizMode::Standard.pause_limits().max_pauses);
+        assert!(stored.pauses.iter().all(|p| p.resumed_at.is_some()));
+    }
+
+    #[tokio::test]
+    async fn test_idle_quizzes_are_abandoned_and_purged() {
+        // Arrange: a finished quiz, an idle one and an active one, plus a quiz stored before
+        // statuses existed
+        let ctx = setup().await;
+        let user = create_test_user(&ctx.db, Role::User, "wanderer", "+12015550255").await;
+        let caller = Caller { user_id: user.id, is_admin: false };
+        let category_id = seed_category(&ctx, 2).await;
+        let quiz_service = &ctx.services.quiz_service;
+        let user_service = &ctx.services.user_service;
+        let start = || quiz_service.start_quiz(user.id, category_id, Difficulty::Beginner, 2, true, false);
+        let finished = start().await.unwrap();
+        let question = quiz_service.next_question(caller, finished.id.unwrap()).await.unwrap();
+        let question_id = ObjectId::parse_str(&question.question_id).unwrap();
+        quiz_service.submit_answer(caller, finished.id.unwrap(), question_id, "A".into(), None, None).await.unwrap();
+        let score = quiz_service.finish_quiz(caller, user_service, finished.id.unwrap(), None).await.unwrap().score;
+        let idle = start().await.unwrap();
+        let active = start().await.unwrap();
+        let quizzes = ctx.db.collection::<bson::Document>("quizzes");
+        let two_days_ago = chrono::Utc::now() - chrono::Duration::days(2);
+        let (two_days_ago, last_active) = (two_days_ago.to_rfc3339(), bson::DateTime::from_chrono(two_days_ago));
+        quizzes
+            .update_one(
+                bson::doc! { "_id": idle.id },
+                bson::doc! { "$set": { "start_time": &two_days_ago, "last_activity_at": last_active } },
+            )
+            .await
+            .unwrap();
+        let legacy = start().await.unwrap().id;
+        quizzes
+            .update_one(
+                bson::doc! { "_id": legacy },
+                bson::doc! { "$set": { "start_time": &two_days_ago, "paused": true }, "$unset": { "status": "", "last_activity_at": "" } },
+            )
+            .await
+            .unwrap();
+
+        // Act
+        let backfilled = quiz_service.backfill_statuses().await.unwrap();
+        let backfilled_activity = quiz_service.backfill_last_activity().await.unwrap();
+        let abandoned = quiz_service.abandon_idle_quizzes(chrono::Duration::hours(24)).await.unwrap();
+        let late_answer = quiz_service.next_question(caller, idle.id.unwrap()).await;
+        let late_finish = quiz_service.finish_quiz(caller, user_service, idle.id.unwrap(), None).await;
+        let kept = quiz_service.purge_abandoned_quizzes(chrono::Duration::days(30)).await.unwrap();
+        let purged = quiz_service.purge_abandoned_quizzes(chrono::Duration::zero()).await.unwrap();
+
+        // Assert: only the idle quizzes were closed, and they never reached the stats
+        assert_eq!((backfilled, backfilled_activity), (1, 1));
+        assert_eq!(abandoned, 2);
+        assert!(matches!(late_answer, Err(QuizError::QuizAbandoned)));
+        assert!(matches!(late_finish, Err(QuizError::QuizAbandoned)));
+        assert_eq!((kept, purged), (0, 2));
+        assert!(quizzes.find_one(bson::doc! { "_id": legacy }).await.unwrap().is_none());
+        assert!(matches!(quiz_service.get_quiz(caller, idle.id.unwrap()).await, Err(QuizError::NotFound)));
+        assert_eq!(quiz_service.get_quiz(caller, active.id.unwrap()).await.unwrap().status, QuizStatus::InProgress);
+        assert_eq!(quiz_service.get_quiz(caller, finished.id.unwrap()).await.unwrap().status, QuizStatus::Finished);
+        let leaderboard = crate::services::leaderboard_service::LeaderboardService::new(ctx.db.clone())
+            .get_leaderboard(category_id)
+            .await
+            .unwrap();
+        assert_eq!(leaderboard[0].score, score);
+        assert_eq!(user_service.get_user(user.id).await.unwrap().quiz_history.len(), 1);
+    }
+}
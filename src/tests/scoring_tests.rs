@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use crate::models::{
+        question::Difficulty,
+        scoring::{ScoringPolicy, StreakBonus, TimeBonus},
+    };
+    use crate::services::scoring::score;
+
+    fn points(policy: &ScoringPolicy, credit: f64, taken_secs: i64, late: bool, streak: usize) -> i32 {
+        score(policy, &Difficulty::Intermediate, credit, Duration::seconds(taken_secs), Duration::seconds(30), late, streak).points
+    }
+
+    #[test]
+    fn test_default_policy_matches_fixed_scoring() {
+        let policy = ScoringPolicy::default();
+
+        assert_eq!(points(&policy, 1.0, 5, false, 0), 20);
+        assert_eq!(points(&policy, 1.0, 20, false, 3), 10);
+        assert_eq!(points(&policy, 0.5, 5, false, 0), 0);
+        assert_eq!(points(&policy, 1.0, 5, true, 0), 0);
+    }
+
+    #[test]
+    fn test_time_decay_streaks_and_penalties() {
+        let policy = ScoringPolicy {
+            time_bonus: TimeBonus::LinearDecay { max_points: 30 },
+            streak: Some(StreakBonus { step: 0.5, max_multiplier: 2.0 }),
+            wrong_penalty: 4,
+            ..Default::default()
+        };
+
+        assert_eq!(points(&policy, 1.0, 0, false, 0), 40);
+        assert_eq!(points(&policy, 1.0, 15, false, 0), 25);
+        assert_eq!(points(&policy, 1.0, 30, false, 1), 15);
+        // capped at twice the points
+        assert_eq!(points(&policy, 1.0, 30, false, 5), 20);
+        assert_eq!(points(&policy, 0.0, 3, false, 5), -4);
+        // late answers score nothing but are not penalized
+        assert_eq!(points(&policy, 0.0, 40, true, 0), 0);
+    }
+
+    #[test]
+    fn test_partial_credit() {
+        let policy = ScoringPolicy { time_bonus: TimeBonus::None, partial_credit: true, wrong_penalty: 2, ..Default::default() };
+
+        let partial = score(&policy, &Difficulty::Advanced, 0.5, Duration::seconds(5), Duration::seconds(30), false, 0);
+        assert_eq!(partial.points, 10);
+        assert!(!partial.correct);
+        assert_eq!(points(&policy, 0.0, 5, false, 0), -2);
+    }
+
+    #[test]
+    fn test_policy_validation() {
+        assert!(ScoringPolicy::default().validate().is_ok());
+        assert!(ScoringPolicy { wrong_penalty: -1, ..Default::default() }.validate().is_err());
+        assert!(ScoringPolicy { time_bonus: TimeBonus::Flat { points: 5, within_fraction: 2.0 }, ..Default::default() }.validate().is_err());
+        assert!(ScoringPolicy { streak: Some(StreakBonus { step: 0.1, max_multiplier: 0.5 }), ..Default::default() }.validate().is_err());
+    }
+}
@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use crate::services::spaced_repetition::{needs_review, next, quality, Schedule, MIN_EASE};
+
+    #[test]
+    fn test_answers_are_graded_by_correctness_and_speed() {
+        let timer = Duration::seconds(40);
+        assert_eq!(quality(false, false, Duration::seconds(5), timer), 1);
+        assert_eq!(quality(true, true, Duration::seconds(45), timer), 1);
+        assert_eq!(quality(true, false, Duration::seconds(35), timer), 3);
+        assert_eq!(quality(true, false, Duration::seconds(20), timer), 4);
+        assert_eq!(quality(true, false, Duration::seconds(10), timer), 5);
+
+        // wrong and slow answers put a question into review, good ones do not
+        assert!(needs_review(1));
+        assert!(needs_review(3));
+        assert!(!needs_review(4));
+    }
+
+    #[test]
+    fn test_intervals_grow_until_a_question_is_missed() {
+        let first = next(Schedule::default(), 4);
+        let second = next(first, 4);
+        let third = next(second, 4);
+        assert_eq!((first.interval_days, first.repetitions), (1, 1));
+        assert_eq!((second.interval_days, second.repetitions), (6, 2));
+        assert_eq!((third.interval_days, third.repetitions), (15, 3));
+
+        // a miss starts the schedule over and makes the question harder
+        let missed = next(third, 1);
+        assert_eq!((missed.interval_days, missed.repetitions), (1, 0));
+        assert!(missed.ease_factor < third.ease_factor);
+    }
+
+    #[test]
+    fn test_ease_factor_has_a_floor() {
+        let schedule = (0..10).fold(Schedule::default(), |schedule, _| next(schedule, 0));
+        assert_eq!(schedule.ease_factor, MIN_EASE);
+        assert!(next(Schedule::default(), 5).ease_factor > Schedule::default().ease_factor);
+    }
+}
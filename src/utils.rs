@@ -1,5 +1,5 @@
 use argon2::{self, password_hash::{PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng}, Argon2};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use phonenumber::{country, metadata::DATABASE, Mode, Type};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -8,6 +8,9 @@ pub struct Claims {
     pub sub: String,  // User ID
     pub role: String,
     pub exp: usize,
+    pub jti: String,  // Token ID, checked against the revocation list
+    pub iss: String,
+    pub aud: String,
 }
 
 pub fn hash_password(password: &str) -> String {
@@ -23,17 +26,19 @@ pub fn verify_password(hash: &str, password: &str) -> bool {
     Argon2::default().verify_password(password.as_bytes(), &parsed_hash).is_ok()
 }
 
-pub fn generate_jwt(user_id: &str, role: &str, secret: &str) -> String {
-    let expiration = chrono::Utc::now() + chrono::Duration::hours(24);
-    let claims = Claims {
-        sub: user_id.to_string(),
-        role: role.to_string(),
-        exp: expiration.timestamp() as usize,
-    };
-    encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_ref())).unwrap()
-}
+/// Normalize a user-supplied phone number to E.164 (e.g. "+2348012345678").
+/// Numbers without a leading `+` are read as national numbers of `default_region`.
+/// Only numbers that can receive SMS (mobile, or mobile-or-fixed where the plan can't tell) pass.
+pub fn normalize_phone(raw: &str, default_region: country::Id) -> Result<String, String> {
+    let number = phonenumber::parse(Some(default_region), raw.trim())
+        .map_err(|_| format!("Invalid phone number: {}", raw))?;
+
+    if !number.is_valid() {
+        return Err(format!("Invalid phone number: {}", raw));
+    }
 
-pub fn validate_jwt(token: &str, secret: &str) -> Option<Claims> {
-    decode::<Claims>(token, &DecodingKey::from_secret(secret.as_ref()), &Validation::default())
-        .map(|data| data.claims).ok()
-}
\ No newline at end of file
+    match number.number_type(&DATABASE) {
+        Type::Mobile | Type::FixedLineOrMobile => Ok(number.format().mode(Mode::E164).to_string()),
+        _ => Err(format!("Phone number must be a mobile number: {}", raw)),
+    }
+}